@@ -1,143 +1,588 @@
 use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Sample, SampleFormat};
+use crate::config::Config;
+use cpal::{
+	BufferSize, FromSample, Sample, SampleFormat, SampleRate, SupportedBufferSize, SupportedStreamConfig,
+};
 use parking_lot::Mutex;
 use rubato::{FftFixedIn, Resampler};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::sync::mpsc;
 
-pub struct RecordingSession {
-	samples: Arc<Mutex<Vec<f32>>>,
-	sample_rate: u32,
-	is_recording: Arc<AtomicBool>,
+/// Rate recordings and audio files are converted to for transcription
+pub const TRANSCRIPTION_SAMPLE_RATE: u32 = 16000;
+
+/// RMS level above which a chunk of audio is considered speech
+const SPEECH_RMS_THRESHOLD: f32 = 0.01;
+
+/// Stops a recording once speech has been followed by a period of silence
+pub struct AutoStop {
+	pub silence_ms: u32,
+	pub on_silence: Box<dyn FnOnce() + Send>,
 }
 
-impl RecordingSession {
-	pub fn start() -> Result<Self> {
-		let host = cpal::default_host();
-		let device = host
-			.default_input_device()
-			.context("No input device available")?;
-
-		let config = device
-			.default_input_config()
-			.context("Failed to get default input config")?;
-
-		let sample_rate = config.sample_rate().0;
-		let channels = config.channels() as usize;
-
-		let samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
-		let is_recording = Arc::new(AtomicBool::new(true));
-
-		let samples_clone = Arc::clone(&samples);
-		let is_recording_clone = Arc::clone(&is_recording);
-
-		let err_fn = |err| eprintln!("Audio stream error: {}", err);
-
-		let stream = match config.sample_format() {
-			SampleFormat::F32 => device.build_input_stream(
-				&config.into(),
-				move |data: &[f32], _: &_| {
-					if is_recording_clone.load(Ordering::SeqCst) {
-						let mono: Vec<f32> = if channels > 1 {
-							data.chunks(channels)
-								.map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
-								.collect()
-						} else {
-							data.to_vec()
-						};
-						samples_clone.lock().extend(mono);
-					}
-				},
-				err_fn,
-				None,
-			)?,
-			SampleFormat::I16 => {
-				let samples_clone = Arc::clone(&samples);
-				let is_recording_clone = Arc::clone(&is_recording);
-				device.build_input_stream(
-					&config.into(),
-					move |data: &[i16], _: &_| {
-						if is_recording_clone.load(Ordering::SeqCst) {
-							let mono: Vec<f32> = if channels > 1 {
-								data.chunks(channels)
-									.map(|chunk| {
-										chunk.iter().map(|&s| s.to_float_sample()).sum::<f32>()
-											/ channels as f32
-									})
-									.collect()
-							} else {
-								data.iter().map(|&s| s.to_float_sample()).collect()
-							};
-							samples_clone.lock().extend(mono);
-						}
-					},
-					err_fn,
-					None,
-				)?
+struct SilenceDetector {
+	silent_samples_needed: usize,
+	silent_samples: usize,
+	speech_detected: bool,
+	on_silence: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl SilenceDetector {
+	fn new(auto_stop: AutoStop, sample_rate: u32) -> Self {
+		Self {
+			silent_samples_needed: (auto_stop.silence_ms as u64 * sample_rate as u64 / 1000) as usize,
+			silent_samples: 0,
+			speech_detected: false,
+			on_silence: Some(auto_stop.on_silence),
+		}
+	}
+
+	fn process(&mut self, samples: &[f32]) {
+		if rms(samples) >= SPEECH_RMS_THRESHOLD {
+			self.speech_detected = true;
+			self.silent_samples = 0;
+			return;
+		}
+
+		// Never stop before anything was said
+		if !self.speech_detected {
+			return;
+		}
+
+		self.silent_samples += samples.len();
+		if self.silent_samples >= self.silent_samples_needed {
+			if let Some(on_silence) = self.on_silence.take() {
+				// Keep the audio callback free of blocking work
+				std::thread::spawn(on_silence);
 			}
-			SampleFormat::U16 => {
-				let samples_clone = Arc::clone(&samples);
-				let is_recording_clone = Arc::clone(&is_recording);
-				device.build_input_stream(
-					&config.into(),
-					move |data: &[u16], _: &_| {
-						if is_recording_clone.load(Ordering::SeqCst) {
-							let mono: Vec<f32> = if channels > 1 {
-								data.chunks(channels)
-									.map(|chunk| {
-										chunk.iter().map(|&s| s.to_float_sample()).sum::<f32>()
-											/ channels as f32
-									})
-									.collect()
-							} else {
-								data.iter().map(|&s| s.to_float_sample()).collect()
-							};
-							samples_clone.lock().extend(mono);
-						}
-					},
-					err_fn,
-					None,
-				)?
+		}
+	}
+}
+
+pub fn rms(samples: &[f32]) -> f32 {
+	if samples.is_empty() {
+		return 0.0;
+	}
+	(samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Noise floors above this mean the room wasn't quiet during calibration,
+/// most likely because someone was talking
+pub const MAX_PLAUSIBLE_NOISE_FLOOR: f32 = 0.05;
+
+/// Ambient noise level of a recording: the median RMS of its 50 ms
+/// chunks, so a brief cough or click doesn't raise it
+pub fn noise_floor(samples: &[f32]) -> f32 {
+	let chunk_len = (TRANSCRIPTION_SAMPLE_RATE / 20) as usize;
+	let mut levels: Vec<f32> = samples.chunks(chunk_len).map(rms).collect();
+	if levels.is_empty() {
+		return 0.0;
+	}
+	levels.sort_by(f32::total_cmp);
+	levels[levels.len() / 2]
+}
+
+/// Downsamples audio to `buckets` peak amplitudes for drawing a waveform
+pub fn peak_envelope(samples: &[f32], buckets: usize) -> Vec<f32> {
+	if samples.is_empty() || buckets == 0 {
+		return Vec::new();
+	}
+
+	let bucket_size = samples.len().div_ceil(buckets);
+	samples
+		.chunks(bucket_size)
+		.map(|chunk| chunk.iter().fold(0.0f32, |peak, s| peak.max(s.abs())))
+		.collect()
+}
+
+/// Level from which a sample is counted as clipped
+const CLIP_LEVEL: f32 = 0.99;
+
+/// Share of clipped samples above which the input is likely too loud
+pub const CLIPPING_WARNING_FRACTION: f32 = 0.001;
+
+/// A finished recording
+pub struct Recording {
+	/// Mono samples at 16 kHz
+	pub samples: Vec<f32>,
+	/// Share of the captured samples at or near full scale
+	pub clipped_fraction: f32,
+}
+
+/// State shared between a capture stream's callback and the recording using it
+struct CaptureShared {
+	samples: Mutex<Vec<f32>>,
+	is_recording: AtomicBool,
+	/// First error reported by the input stream, if any
+	stream_error: Mutex<Option<String>>,
+	detector: Mutex<Option<SilenceDetector>>,
+	sample_rate: u32,
+	device_name: String,
+}
+
+impl CaptureShared {
+	fn push(&self, mono: Vec<f32>) {
+		if let Some(detector) = self.detector.lock().as_mut() {
+			detector.process(&mono);
+		}
+		self.samples.lock().extend(mono);
+	}
+}
+
+/// Device settings a capture stream is opened with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptureSettings {
+	/// Audio host name, `None` for the platform default
+	pub audio_host: Option<String>,
+	/// Preferred capture rate, `None` for the device default
+	pub sample_rate: Option<u32>,
+	/// Frames per callback, `None` lets the OS choose
+	pub buffer_frames: Option<u32>,
+	/// Input devices to use in order of preference when connected
+	pub preferred_devices: Vec<String>,
+	/// Input devices the user marked as capturing system audio
+	pub loopback_devices: Vec<String>,
+}
+
+impl CaptureSettings {
+	pub fn from_config(config: &Config) -> Self {
+		Self {
+			audio_host: config.audio_host.clone(),
+			sample_rate: config.capture_sample_rate,
+			buffer_frames: config.audio_buffer_frames,
+			preferred_devices: config.preferred_devices.clone(),
+			loopback_devices: config.loopback_devices.clone(),
+		}
+	}
+}
+
+/// A capture stream kept running between recordings
+struct PersistentCapture {
+	shared: Arc<CaptureShared>,
+	settings: CaptureSettings,
+	/// Dropped to stop the thread that owns the stream
+	_stop: mpsc::Sender<()>,
+}
+
+static PERSISTENT_CAPTURE: Mutex<Option<PersistentCapture>> = Mutex::new(None);
+
+/// Opens the always-on capture stream ahead of the first recording, or
+/// reopens it if the settings changed or it failed.
+pub fn open_persistent_stream(settings: &CaptureSettings) -> Result<()> {
+	persistent_capture(settings).map(|_| ())
+}
+
+/// Closes the always-on capture stream, releasing the microphone
+pub fn close_persistent_stream() {
+	*PERSISTENT_CAPTURE.lock() = None;
+}
+
+fn persistent_capture(settings: &CaptureSettings) -> Result<Arc<CaptureShared>> {
+	let mut persistent = PERSISTENT_CAPTURE.lock();
+
+	if let Some(capture) = persistent.as_ref()
+		&& capture.settings == *settings
+		&& capture.shared.stream_error.lock().is_none()
+	{
+		return Ok(Arc::clone(&capture.shared));
+	}
+
+	*persistent = None;
+
+	let (result_tx, result_rx) = mpsc::channel();
+	let (stop_tx, stop_rx) = mpsc::channel::<()>();
+	let thread_settings = settings.clone();
+
+	// Streams can't be moved between threads on every platform, so one
+	// thread owns it until the stop sender is dropped
+	std::thread::spawn(move || match open_capture(&thread_settings) {
+		Ok((stream, shared)) => {
+			let _ = result_tx.send(Ok(shared));
+			let _ = stop_rx.recv();
+			drop(stream);
+		}
+		Err(e) => {
+			let _ = result_tx.send(Err(e));
+		}
+	});
+
+	let shared = result_rx.recv().context("Audio thread exited")??;
+	*persistent = Some(PersistentCapture {
+		shared: Arc::clone(&shared),
+		settings: settings.clone(),
+		_stop: stop_tx,
+	});
+
+	Ok(shared)
+}
+
+/// Opens and starts an input stream that keeps samples only while
+/// `is_recording` is set.
+fn open_capture(settings: &CaptureSettings) -> Result<(cpal::Stream, Arc<CaptureShared>)> {
+	let host = select_host(settings.audio_host.as_deref());
+	let device = select_input_device(&host, &settings.preferred_devices)?;
+	let device_name = device.name().unwrap_or_else(|_| "Unknown device".to_string());
+	let loopback = is_loopback_device(&device_name, &settings.loopback_devices);
+	eprintln!(
+		"Capturing from {}{}",
+		device_name,
+		if loopback { " (system audio)" } else { "" }
+	);
+
+	let default_config = device
+		.default_input_config()
+		.context("Failed to get default input config")?;
+
+	// A loopback source mirrors the output mix, asking it for another rate
+	// fails or makes the sound server resample, so it is captured as is
+	// and downmixed and resampled like any other source
+	let requested_rate = settings.sample_rate.filter(|_| !loopback);
+	let config = match requested_rate {
+		Some(rate) => match find_config_with_rate(&device, &default_config, rate) {
+			Some(config) => config,
+			None => {
+				eprintln!(
+					"Input device does not support {} Hz, using {} Hz",
+					rate,
+					default_config.sample_rate().0
+				);
+				default_config
 			}
-			_ => anyhow::bail!("Unsupported sample format"),
+		},
+		None => default_config,
+	};
+	eprintln!(
+		"Capturing {} channel(s) at {} Hz",
+		config.channels(),
+		config.sample_rate().0
+	);
+
+	let shared = Arc::new(CaptureShared {
+		samples: Mutex::new(Vec::new()),
+		is_recording: AtomicBool::new(false),
+		stream_error: Mutex::new(None),
+		detector: Mutex::new(None),
+		sample_rate: config.sample_rate().0,
+		device_name,
+	});
+
+	let mut stream_config = config.config();
+	stream_config.buffer_size = buffer_size_for(settings.buffer_frames, config.buffer_size());
+	match stream_config.buffer_size {
+		BufferSize::Fixed(frames) => eprintln!("Capturing with a buffer of {} frames", frames),
+		BufferSize::Default => eprintln!("Capturing with the default buffer size"),
+	}
+
+	let stream = match config.sample_format() {
+		SampleFormat::F32 => build_input_stream::<f32>(&device, &stream_config, &shared)?,
+		SampleFormat::I16 => build_input_stream::<i16>(&device, &stream_config, &shared)?,
+		SampleFormat::U16 => build_input_stream::<u16>(&device, &stream_config, &shared)?,
+		SampleFormat::I8 => build_input_stream::<i8>(&device, &stream_config, &shared)?,
+		SampleFormat::I32 => build_input_stream::<i32>(&device, &stream_config, &shared)?,
+		SampleFormat::F64 => build_input_stream::<f64>(&device, &stream_config, &shared)?,
+		format => anyhow::bail!(
+			"Unsupported sample format {} on {}, try another input device",
+			format,
+			shared.device_name
+		),
+	};
+
+	stream.play().context("Failed to start audio stream")?;
+
+	Ok((stream, shared))
+}
+
+/// The first connected device from `preferred`, or the host's default
+fn select_input_device(host: &cpal::Host, preferred: &[String]) -> Result<cpal::Device> {
+	if !preferred.is_empty() {
+		let devices: Vec<cpal::Device> = host.input_devices()?.collect();
+		let names: Vec<String> = devices
+			.iter()
+			.map(|d| d.name().unwrap_or_default())
+			.collect();
+
+		if let Some(index) = pick_preferred(preferred, &names)
+			&& let Some(device) = devices.into_iter().nth(index)
+		{
+			return Ok(device);
+		}
+		eprintln!("None of the preferred input devices are connected, using the default");
+	}
+
+	host.default_input_device()
+		.context("No input device available")
+}
+
+/// Index in `available` of the highest priority entry of `preferred`,
+/// names compared case-insensitively
+fn pick_preferred(preferred: &[String], available: &[String]) -> Option<usize> {
+	preferred.iter().find_map(|wanted| {
+		available
+			.iter()
+			.position(|name| name.eq_ignore_ascii_case(wanted.trim()))
+	})
+}
+
+/// Uses the requested buffer size when the device reports supporting it,
+/// and the OS default otherwise
+fn buffer_size_for(requested: Option<u32>, supported: &SupportedBufferSize) -> BufferSize {
+	let Some(frames) = requested else {
+		return BufferSize::Default;
+	};
+
+	match supported {
+		SupportedBufferSize::Range { min, max } if (*min..=*max).contains(&frames) => {
+			BufferSize::Fixed(frames)
+		}
+		SupportedBufferSize::Range { min, max } => {
+			eprintln!(
+				"Buffer size of {} frames is outside the supported {}-{}, using the default",
+				frames, min, max
+			);
+			BufferSize::Default
+		}
+		SupportedBufferSize::Unknown => {
+			eprintln!("Input device doesn't report its buffer sizes, using the default");
+			BufferSize::Default
+		}
+	}
+}
+
+fn build_input_stream<T>(
+	device: &cpal::Device,
+	config: &cpal::StreamConfig,
+	shared: &Arc<CaptureShared>,
+) -> Result<cpal::Stream>
+where
+	T: cpal::SizedSample,
+	f32: FromSample<T>,
+{
+	let channels = config.channels as usize;
+
+	let data_shared = Arc::clone(shared);
+	let on_data = move |data: &[T], _: &_| {
+		if data_shared.is_recording.load(Ordering::SeqCst) {
+			let mono: Vec<f32> = if channels > 1 {
+				data.chunks(channels)
+					.map(|chunk| {
+						chunk.iter().map(|&s| s.to_sample::<f32>()).sum::<f32>() / channels as f32
+					})
+					.collect()
+			} else {
+				data.iter().map(|&s| s.to_sample::<f32>()).collect()
+			};
+			data_shared.push(mono);
+		}
+	};
+
+	let error_shared = Arc::clone(shared);
+	let on_error = move |err: cpal::StreamError| {
+		eprintln!("Audio stream error: {}", err);
+		let message = match err {
+			cpal::StreamError::DeviceNotAvailable => "Microphone disconnected".to_string(),
+			other => format!("Audio stream error: {}", other),
 		};
+		error_shared.stream_error.lock().get_or_insert(message);
+	};
+
+	Ok(device.build_input_stream(config, on_data, on_error, None)?)
+}
+
+/// Rate the live PCM of a recording is roughly downsampled to for streaming
+pub const PCM_STREAM_RATE: u32 = 8000;
+
+/// Reads the audio an ongoing recording captured since the previous read
+pub struct PcmTap {
+	shared: Arc<CaptureShared>,
+	position: usize,
+}
+
+impl PcmTap {
+	/// Rate of the samples returned by `read`
+	pub fn sample_rate(&self) -> u32 {
+		self.shared.sample_rate / self.factor() as u32
+	}
 
-		stream.play().context("Failed to start audio stream")?;
+	fn factor(&self) -> usize {
+		(self.shared.sample_rate / PCM_STREAM_RATE).max(1) as usize
+	}
+
+	/// New samples since the last read, `None` once the recording is over
+	pub fn read(&mut self) -> Option<Vec<f32>> {
+		if !self.shared.is_recording.load(Ordering::SeqCst) {
+			return None;
+		}
+
+		let factor = self.factor();
+		let samples = self.shared.samples.lock();
+		// The buffer was cleared for a newer recording on a kept-open stream
+		if self.position > samples.len() {
+			return None;
+		}
+
+		// Whole blocks only, the rest is picked up by the next read
+		let available = (samples.len() - self.position) / factor * factor;
+		let chunk = &samples[self.position..self.position + available];
+		self.position += available;
+
+		Some(average_blocks(chunk, factor))
+	}
+}
 
-		std::mem::forget(stream);
+/// Averages each block of `factor` samples into one
+fn average_blocks(samples: &[f32], factor: usize) -> Vec<f32> {
+	samples
+		.chunks(factor)
+		.map(|block| block.iter().sum::<f32>() / block.len() as f32)
+		.collect()
+}
+
+pub struct RecordingSession {
+	shared: Arc<CaptureShared>,
+	/// Whether the stream stays open after this recording
+	persistent: bool,
+}
+
+impl RecordingSession {
+	/// Starts recording from the default input device. With
+	/// `keep_stream_open` the stream is reused across recordings.
+	pub fn start(
+		auto_stop: Option<AutoStop>,
+		settings: &CaptureSettings,
+		keep_stream_open: bool,
+	) -> Result<Self> {
+		let shared = if keep_stream_open {
+			let shared = persistent_capture(settings)?;
+			shared.samples.lock().clear();
+			shared
+		} else {
+			close_persistent_stream();
+			let (stream, shared) = open_capture(settings)?;
+			std::mem::forget(stream);
+			shared
+		};
+
+		*shared.detector.lock() =
+			auto_stop.map(|auto_stop| SilenceDetector::new(auto_stop, shared.sample_rate));
+		shared.is_recording.store(true, Ordering::SeqCst);
 
 		Ok(Self {
-			samples,
-			sample_rate,
-			is_recording,
+			shared,
+			persistent: keep_stream_open,
 		})
 	}
 
-	pub fn stop(self) -> Result<Vec<f32>> {
-		self.is_recording.store(false, Ordering::SeqCst);
+	/// Name of the input device being recorded from
+	pub fn device_name(&self) -> &str {
+		&self.shared.device_name
+	}
+
+	pub fn pcm_tap(&self) -> PcmTap {
+		PcmTap {
+			shared: Arc::clone(&self.shared),
+			position: 0,
+		}
+	}
+
+	/// Ends the recording and returns 16 kHz samples boosted by `input_gain_db`
+	pub fn stop(self, input_gain_db: f32) -> Result<Recording> {
+		self.shared.is_recording.store(false, Ordering::SeqCst);
 
 		std::thread::sleep(std::time::Duration::from_millis(100));
 
-		let samples = self.samples.lock().clone();
+		*self.shared.detector.lock() = None;
+
+		if let Some(error) = self.shared.stream_error.lock().clone() {
+			if self.persistent {
+				close_persistent_stream();
+			}
+			anyhow::bail!(error);
+		}
+
+		let mut samples = std::mem::take(&mut *self.shared.samples.lock());
 
 		if samples.is_empty() {
 			anyhow::bail!("No audio recorded");
 		}
 
-		resample_to_16khz(&samples, self.sample_rate)
+		// Measured before our own gain, which soft-clips instead
+		let clipped_fraction = clipped_fraction(&samples);
+		apply_gain(&mut samples, input_gain_db);
+
+		Ok(Recording {
+			samples: resample_to(&samples, self.shared.sample_rate, TRANSCRIPTION_SAMPLE_RATE)?,
+			clipped_fraction,
+		})
 	}
 }
 
-fn resample_to_16khz(samples: &[f32], source_rate: u32) -> Result<Vec<f32>> {
-	const TARGET_RATE: u32 = 16000;
+/// Length of `benchmark_clip`, in seconds
+const BENCHMARK_CLIP_SECS: u32 = 10;
+
+/// Deterministic voice-like audio at the transcription sample rate, used
+/// to time decoding without needing a recording
+pub fn benchmark_clip() -> Vec<f32> {
+	let rate = TRANSCRIPTION_SAMPLE_RATE as f32;
+	// Small LCG so the noise is the same on every run
+	let mut seed: u32 = 0x2545_f491;
+
+	(0..TRANSCRIPTION_SAMPLE_RATE * BENCHMARK_CLIP_SECS)
+		.map(|i| {
+			let t = i as f32 / rate;
+			// A drifting fundamental with harmonics, pulsed like syllables
+			let pitch = 140.0 + 30.0 * (t * 0.7 * std::f32::consts::TAU).sin();
+			let voice: f32 = (1..=4)
+				.map(|harmonic| {
+					(t * pitch * harmonic as f32 * std::f32::consts::TAU).sin() / harmonic as f32
+				})
+				.sum();
+			let envelope = (t * 4.0 * std::f32::consts::TAU).sin().max(0.0);
+
+			seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+			let noise = (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+
+			0.15 * voice * envelope + 0.02 * noise
+		})
+		.collect()
+}
+
+/// Sample rates accepted for audio handed in from outside, covering
+/// telephone quality up to high-end interfaces
+const EXTERNAL_SAMPLE_RATES: std::ops::RangeInclusive<u32> = 4000..=384_000;
 
-	if source_rate == TARGET_RATE {
+/// Checks mono audio provided by the frontend before it's resampled and
+/// transcribed
+pub fn validate_external_samples(samples: &[f32], sample_rate: u32) -> Result<()> {
+	if samples.is_empty() {
+		anyhow::bail!("No samples provided");
+	}
+	if !EXTERNAL_SAMPLE_RATES.contains(&sample_rate) {
+		anyhow::bail!(
+			"Sample rate must be between {} and {} Hz, got {}",
+			EXTERNAL_SAMPLE_RATES.start(),
+			EXTERNAL_SAMPLE_RATES.end(),
+			sample_rate
+		);
+	}
+	if samples.iter().any(|sample| !sample.is_finite()) {
+		anyhow::bail!("Samples must be finite numbers");
+	}
+	Ok(())
+}
+
+/// Converts mono audio from `source_rate` to `target_rate`
+pub fn resample_to(samples: &[f32], source_rate: u32, target_rate: u32) -> Result<Vec<f32>> {
+	if source_rate == target_rate {
 		return Ok(samples.to_vec());
 	}
 
-	let mut resampler = FftFixedIn::<f32>::new(source_rate as usize, TARGET_RATE as usize, 1024, 2, 1)
-		.context("Failed to create resampler")?;
+	let mut resampler =
+		FftFixedIn::<f32>::new(source_rate as usize, target_rate as usize, 1024, 2, 1)
+			.context("Failed to create resampler")?;
 
 	let mut output = Vec::new();
 	let input_frames_needed = resampler.input_frames_next();
@@ -160,11 +605,495 @@ fn resample_to_16khz(samples: &[f32], source_rate: u32) -> Result<Vec<f32>> {
 	Ok(output)
 }
 
-pub fn list_input_devices() -> Result<Vec<String>> {
-	let host = cpal::default_host();
+/// Interprets text, such as clipboard contents, as the path of a WAV file.
+/// Surrounding quotes and a `file://` prefix are stripped.
+pub fn audio_path_from_text(text: &str) -> Result<PathBuf> {
+	let text = text.trim();
+	let text = text
+		.strip_prefix('"')
+		.and_then(|t| t.strip_suffix('"'))
+		.unwrap_or(text);
+	let text = text.strip_prefix("file://").unwrap_or(text);
+
+	if text.is_empty() {
+		anyhow::bail!("Clipboard does not contain a file path");
+	}
+
+	let path = PathBuf::from(text);
+	if !path.is_file() {
+		anyhow::bail!("Clipboard does not contain a path to an existing file: {}", text);
+	}
+
+	let is_wav = path
+		.extension()
+		.is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+	if !is_wav {
+		anyhow::bail!("Only WAV files can be transcribed: {}", path.display());
+	}
+
+	Ok(path)
+}
+
+/// Start of the names of recordings saved by the app, followed by a
+/// timestamp so they sort oldest first
+pub const RECORDING_FILE_PREFIX: &str = "recording-";
+
+/// Deletes all but the `keep` newest recordings saved by the app in `dir`,
+/// leaving any other files alone. Returns how many were deleted.
+pub fn prune_recordings(dir: &Path, keep: usize) -> Result<usize> {
+	let mut recordings: Vec<PathBuf> = std::fs::read_dir(dir)
+		.context("Failed to read recordings directory")?
+		.filter_map(|entry| entry.ok())
+		.map(|entry| entry.path())
+		.filter(|path| {
+			path.is_file()
+				&& path.extension().is_some_and(|ext| ext == "wav")
+				&& path
+					.file_name()
+					.and_then(|name| name.to_str())
+					.is_some_and(|name| name.starts_with(RECORDING_FILE_PREFIX))
+		})
+		.collect();
+	recordings.sort();
+
+	let excess = recordings.len().saturating_sub(keep);
+	for path in &recordings[..excess] {
+		std::fs::remove_file(path)
+			.with_context(|| format!("Failed to delete {}", path.display()))?;
+	}
+	Ok(excess)
+}
+
+/// Writes mono samples at the transcription rate as a 16-bit WAV file
+pub fn write_wav_file(path: &Path, samples: &[f32]) -> Result<()> {
+	let spec = hound::WavSpec {
+		channels: 1,
+		sample_rate: TRANSCRIPTION_SAMPLE_RATE,
+		bits_per_sample: 16,
+		sample_format: hound::SampleFormat::Int,
+	};
+	let mut writer = hound::WavWriter::create(path, spec)
+		.with_context(|| format!("Failed to create audio file: {}", path.display()))?;
+
+	for &sample in samples {
+		writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+	}
+	writer.finalize().context("Failed to write audio file")?;
+
+	Ok(())
+}
+
+/// Reads a WAV file and returns its audio as 16kHz mono samples
+pub fn read_wav_file(path: &Path) -> Result<Vec<f32>> {
+	let mut reader = hound::WavReader::open(path)
+		.with_context(|| format!("Failed to open audio file: {}", path.display()))?;
+	let spec = reader.spec();
+	let channels = spec.channels as usize;
+
+	let interleaved: Vec<f32> = match spec.sample_format {
+		hound::SampleFormat::Float => reader
+			.samples::<f32>()
+			.collect::<Result<_, _>>()
+			.context("Failed to read audio samples")?,
+		hound::SampleFormat::Int => {
+			let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+			reader
+				.samples::<i32>()
+				.map(|s| s.map(|s| s as f32 / scale))
+				.collect::<Result<_, _>>()
+				.context("Failed to read audio samples")?
+		}
+	};
+
+	let mono: Vec<f32> = if channels > 1 {
+		interleaved
+			.chunks(channels)
+			.map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
+			.collect()
+	} else {
+		interleaved
+	};
+
+	if mono.is_empty() {
+		anyhow::bail!("Audio file contains no samples");
+	}
+
+	resample_to(&mono, spec.sample_rate, TRANSCRIPTION_SAMPLE_RATE)
+}
+
+/// Finds a supported input config running at `rate`, preferring the sample
+/// format and channel count of the default config.
+fn find_config_with_rate(
+	device: &cpal::Device,
+	default_config: &SupportedStreamConfig,
+	rate: u32,
+) -> Option<SupportedStreamConfig> {
+	let ranges: Vec<_> = device
+		.supported_input_configs()
+		.ok()?
+		.filter(|range| range.min_sample_rate().0 <= rate && rate <= range.max_sample_rate().0)
+		.collect();
+
+	let score = |range: &cpal::SupportedStreamConfigRange| {
+		(range.sample_format() == default_config.sample_format()) as u8
+			+ (range.channels() == default_config.channels()) as u8
+	};
+
+	ranges
+		.into_iter()
+		.max_by_key(score)
+		.map(|range| range.with_sample_rate(SampleRate(rate)))
+}
+
+/// Returns the host named `name`, e.g. "JACK" or "ALSA", falling back to
+/// the default host when it is unset, unknown or unavailable.
+pub fn select_host(name: Option<&str>) -> cpal::Host {
+	let Some(name) = name else {
+		return cpal::default_host();
+	};
+
+	let id = cpal::available_hosts()
+		.into_iter()
+		.find(|id| id.name().eq_ignore_ascii_case(name));
+
+	match id.map(cpal::host_from_id) {
+		Some(Ok(host)) => host,
+		Some(Err(e)) => {
+			eprintln!("Audio host \"{}\" is unavailable ({}), using the default host", name, e);
+			cpal::default_host()
+		}
+		None => {
+			eprintln!("Unknown audio host \"{}\", using the default host", name);
+			cpal::default_host()
+		}
+	}
+}
+
+pub fn has_input_device(audio_host: Option<&str>) -> bool {
+	select_host(audio_host).default_input_device().is_some()
+}
+
+/// Names of the host's input devices, including the monitor and loopback
+/// sources that capture system audio on hosts that expose them
+pub fn list_input_devices(audio_host: Option<&str>) -> Result<Vec<String>> {
+	let host = select_host(audio_host);
 	let devices: Vec<String> = host
 		.input_devices()?
 		.filter_map(|d| d.name().ok())
 		.collect();
 	Ok(devices)
 }
+
+/// Name fragments of devices that capture what the system plays rather
+/// than a microphone: PulseAudio and PipeWire monitor sources, the Windows
+/// "Stereo Mix" driver and common virtual devices on macOS and Windows
+const LOOPBACK_NAME_HINTS: [&str; 7] = [
+	"monitor of",
+	".monitor",
+	"stereo mix",
+	"what u hear",
+	"loopback",
+	"blackhole",
+	"cable output",
+];
+
+/// Whether `name` is a source of system audio, either marked as one by
+/// the user in `marked` or recognized by its name
+pub fn is_loopback_device(name: &str, marked: &[String]) -> bool {
+	if marked.iter().any(|m| m == name) {
+		return true;
+	}
+	let lower = name.to_lowercase();
+	LOOPBACK_NAME_HINTS.iter().any(|hint| lower.contains(hint))
+}
+
+pub fn clipped_fraction(samples: &[f32]) -> f32 {
+	if samples.is_empty() {
+		return 0.0;
+	}
+	let clipped = samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+	clipped as f32 / samples.len() as f32
+}
+
+/// Level above which boosted samples are compressed instead of clipped
+const SOFT_CLIP_KNEE: f32 = 0.9;
+
+/// Amplifies `samples` by `gain_db`, softly limiting peaks so they stay
+/// below full scale. A gain of 0 dB leaves the samples untouched.
+pub fn apply_gain(samples: &mut [f32], gain_db: f32) {
+	if gain_db == 0.0 {
+		return;
+	}
+
+	let gain = 10f32.powf(gain_db / 20.0);
+	let headroom = 1.0 - SOFT_CLIP_KNEE;
+	for sample in samples.iter_mut() {
+		let boosted = *sample * gain;
+		let magnitude = boosted.abs();
+		*sample = if magnitude <= SOFT_CLIP_KNEE {
+			boosted
+		} else {
+			let compressed = SOFT_CLIP_KNEE + headroom * ((magnitude - SOFT_CLIP_KNEE) / headroom).tanh();
+			compressed.copysign(boosted)
+		};
+	}
+}
+
+/// The audio subsystem used for capture and the alternatives on this platform
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioHostInfo {
+	pub host: String,
+	pub default_input_device: Option<String>,
+	pub available_hosts: Vec<String>,
+}
+
+pub fn get_audio_host_info(audio_host: Option<&str>) -> AudioHostInfo {
+	let host = select_host(audio_host);
+
+	AudioHostInfo {
+		host: host.id().name().to_string(),
+		default_input_device: host.default_input_device().and_then(|d| d.name().ok()),
+		available_hosts: cpal::available_hosts()
+			.into_iter()
+			.map(|id| id.name().to_string())
+			.collect(),
+	}
+}
+
+/// Capabilities of an input device as reported by the host
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceDetails {
+	pub name: String,
+	pub default_sample_rate: Option<u32>,
+	pub channels: Option<u16>,
+	pub supported_sample_formats: Vec<String>,
+	pub is_default: bool,
+	/// Captures system audio rather than a microphone
+	pub is_loopback: bool,
+}
+
+pub fn get_input_device_details(
+	audio_host: Option<&str>,
+	loopback_devices: &[String],
+) -> Result<Vec<DeviceDetails>> {
+	let host = select_host(audio_host);
+	let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+	let details = host
+		.input_devices()?
+		.filter_map(|device| {
+			let name = device.name().ok()?;
+			let default_config = device.default_input_config().ok();
+
+			let mut supported_sample_formats: Vec<String> = Vec::new();
+			if let Ok(configs) = device.supported_input_configs() {
+				for config in configs {
+					let format = config.sample_format().to_string();
+					if !supported_sample_formats.contains(&format) {
+						supported_sample_formats.push(format);
+					}
+				}
+			}
+
+			Some(DeviceDetails {
+				is_default: default_name.as_deref() == Some(name.as_str()),
+				is_loopback: is_loopback_device(&name, loopback_devices),
+				name,
+				default_sample_rate: default_config.as_ref().map(|c| c.sample_rate().0),
+				channels: default_config.as_ref().map(|c| c.channels()),
+				supported_sample_formats,
+			})
+		})
+		.collect();
+
+	Ok(details)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_loopback_devices_by_name_or_mark() {
+		assert!(is_loopback_device("Monitor of Built-in Audio Analog Stereo", &[]));
+		assert!(is_loopback_device("alsa_output.pci-0000_00_1f.3.analog-stereo.monitor", &[]));
+		assert!(is_loopback_device("Stereo Mix (Realtek(R) Audio)", &[]));
+		assert!(is_loopback_device("BlackHole 2ch", &[]));
+		assert!(!is_loopback_device("Built-in Microphone", &[]));
+
+		let marked = vec!["Aggregate Device".to_string()];
+		assert!(is_loopback_device("Aggregate Device", &marked));
+	}
+
+	#[test]
+	fn test_noise_floor_ignores_short_bursts() {
+		let chunk = (TRANSCRIPTION_SAMPLE_RATE / 20) as usize;
+		let mut samples = vec![0.002; chunk * 10];
+		// A loud click over one chunk
+		samples[..chunk].fill(0.8);
+
+		assert!((noise_floor(&samples) - 0.002).abs() < 1e-6);
+		assert_eq!(noise_floor(&[]), 0.0);
+	}
+
+	#[test]
+	fn test_prune_recordings_keeps_newest_and_other_files() {
+		let dir = tempfile::tempdir().unwrap();
+		for name in [
+			"recording-2026-01-01T10-00-00Z.wav",
+			"recording-2026-01-02T10-00-00Z.wav",
+			"recording-2026-01-03T10-00-00Z.wav",
+			"interview.wav",
+			"recording-notes.txt",
+		] {
+			std::fs::write(dir.path().join(name), b"").unwrap();
+		}
+
+		assert_eq!(prune_recordings(dir.path(), 1).unwrap(), 2);
+
+		let mut left: Vec<_> = std::fs::read_dir(dir.path())
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+			.collect();
+		left.sort();
+		assert_eq!(
+			left,
+			["interview.wav", "recording-2026-01-03T10-00-00Z.wav", "recording-notes.txt"]
+		);
+		assert_eq!(prune_recordings(dir.path(), 5).unwrap(), 0);
+	}
+
+	#[test]
+	fn test_validate_external_samples() {
+		assert!(validate_external_samples(&[0.0; 160], 16000).is_ok());
+		assert!(validate_external_samples(&[0.0; 160], 48000).is_ok());
+		assert!(validate_external_samples(&[], 16000).is_err());
+		assert!(validate_external_samples(&[0.0; 160], 0).is_err());
+		assert!(validate_external_samples(&[0.0; 160], 1_000_000).is_err());
+		assert!(validate_external_samples(&[0.0, f32::NAN], 16000).is_err());
+	}
+
+	#[test]
+	fn test_benchmark_clip_is_deterministic_and_in_range() {
+		let clip = benchmark_clip();
+		assert_eq!(clip.len(), (TRANSCRIPTION_SAMPLE_RATE * BENCHMARK_CLIP_SECS) as usize);
+		assert!(clip.iter().all(|sample| sample.abs() <= 1.0));
+		assert!(rms(&clip) > SPEECH_RMS_THRESHOLD);
+		assert_eq!(clip, benchmark_clip());
+	}
+
+	#[test]
+	fn test_clipped_fraction() {
+		assert_eq!(clipped_fraction(&[]), 0.0);
+		assert_eq!(clipped_fraction(&[0.5, -1.0, 0.995, 0.2]), 0.5);
+	}
+
+	#[test]
+	fn test_apply_gain_zero_is_noop() {
+		let mut samples = vec![0.5, -1.2, 0.0];
+		apply_gain(&mut samples, 0.0);
+		assert_eq!(samples, vec![0.5, -1.2, 0.0]);
+	}
+
+	#[test]
+	fn test_apply_gain_boosts_and_soft_clips() {
+		let mut samples = vec![0.1, -0.1, 0.5, -0.5];
+		apply_gain(&mut samples, 6.0);
+
+		assert!((samples[0] - 0.1995).abs() < 1e-3);
+		assert!((samples[1] + 0.1995).abs() < 1e-3);
+		assert!(samples[2] > SOFT_CLIP_KNEE && samples[2] < 1.0);
+		assert!(samples[3] < -SOFT_CLIP_KNEE && samples[3] > -1.0);
+	}
+
+	#[test]
+	fn test_audio_path_from_text() {
+		let dir = tempfile::tempdir().unwrap();
+		let wav = dir.path().join("clip.WAV");
+		let txt = dir.path().join("notes.txt");
+		std::fs::write(&wav, b"").unwrap();
+		std::fs::write(&txt, b"").unwrap();
+
+		let quoted = format!("  \"{}\"\n", wav.display());
+		assert_eq!(audio_path_from_text(&quoted).unwrap(), wav);
+		let url = format!("file://{}", wav.display());
+		assert_eq!(audio_path_from_text(&url).unwrap(), wav);
+
+		assert!(audio_path_from_text("   ").is_err());
+		assert!(audio_path_from_text(&txt.display().to_string()).is_err());
+		assert!(audio_path_from_text(&dir.path().join("missing.wav").display().to_string()).is_err());
+	}
+
+	#[test]
+	fn test_wav_round_trip() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("recording.wav");
+		let samples = vec![0.0, 0.5, -0.5, 1.5];
+
+		write_wav_file(&path, &samples).unwrap();
+		let read = read_wav_file(&path).unwrap();
+
+		assert_eq!(read.len(), samples.len());
+		assert!((read[1] - 0.5).abs() < 1e-3);
+		assert!((read[2] + 0.5).abs() < 1e-3);
+		// Out of range samples are clipped rather than wrapped
+		assert!(read[3] > 0.99);
+	}
+
+	#[test]
+	fn test_resample_to() {
+		let samples: Vec<f32> = (0..4800).map(|i| (i as f32 * 0.01).sin()).collect();
+		assert_eq!(resample_to(&samples, 16000, 16000).unwrap(), samples);
+
+		// Output is padded up to whole resampler chunks
+		let resampled = resample_to(&samples, 48000, 16000).unwrap();
+		assert!(resampled.len() >= 1600 && resampled.len() < 1600 + 1024);
+	}
+
+	#[test]
+	fn test_pick_preferred_follows_priority() {
+		let available = vec!["Built-in Microphone".to_string(), "USB Headset".to_string()];
+		let preferred = vec![
+			"Bluetooth Headset".to_string(),
+			"usb headset".to_string(),
+			"Built-in Microphone".to_string(),
+		];
+
+		assert_eq!(pick_preferred(&preferred, &available), Some(1));
+		assert_eq!(pick_preferred(&preferred[..1], &available), None);
+		assert_eq!(pick_preferred(&[], &available), None);
+	}
+
+	#[test]
+	fn test_buffer_size_for() {
+		let range = SupportedBufferSize::Range { min: 64, max: 4096 };
+		assert_eq!(buffer_size_for(None, &range), BufferSize::Default);
+		assert_eq!(buffer_size_for(Some(256), &range), BufferSize::Fixed(256));
+		assert_eq!(buffer_size_for(Some(8192), &range), BufferSize::Default);
+		assert_eq!(buffer_size_for(Some(256), &SupportedBufferSize::Unknown), BufferSize::Default);
+	}
+
+	#[test]
+	fn test_average_blocks() {
+		assert_eq!(average_blocks(&[0.25, 0.75, -0.5, 0.5, 1.0], 2), vec![0.5, 0.0, 1.0]);
+		assert!(average_blocks(&[], 4).is_empty());
+	}
+
+	#[test]
+	fn test_peak_envelope_empty() {
+		assert!(peak_envelope(&[], 10).is_empty());
+		assert!(peak_envelope(&[0.5], 0).is_empty());
+	}
+
+	#[test]
+	fn test_peak_envelope_takes_absolute_peaks() {
+		let samples = [0.1, -0.8, 0.3, 0.2, -0.1, 0.05];
+		assert_eq!(peak_envelope(&samples, 2), vec![0.8, 0.2]);
+	}
+
+	#[test]
+	fn test_peak_envelope_never_exceeds_bucket_count() {
+		let samples = vec![0.5; 1001];
+		assert!(peak_envelope(&samples, 100).len() <= 100);
+	}
+}
@@ -1,17 +1,253 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Where a finished transcription is delivered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+	/// Copied to the clipboard when `auto_copy` is on
+	#[default]
+	Clipboard,
+	/// Opened in the editor window for review before copying
+	Editor,
+	/// Typed into `target_app` after bringing it to the front, or into the
+	/// focused window when it can't be found
+	#[serde(rename = "target_app")]
+	TargetApp,
+	/// Typed into the focused window as keystrokes
+	Type,
+}
+
+/// How the text is formatted before it is copied or shown in the editor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+	#[default]
+	Plain,
+	/// Every line prefixed with `> `
+	MarkdownQuote,
+	/// Every line prefixed with `- `
+	Bullet,
+}
+
+/// What is put on the clipboard along with the plain text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardFormat {
+	#[default]
+	Plain,
+	/// HTML with the plain text as fallback, for apps that paste rich text
+	Html,
+}
+
+/// Layout of inline segment timestamps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampFormat {
+	/// `[mm:ss]`, minutes keep counting past an hour
+	#[default]
+	#[serde(rename = "mm:ss")]
+	MinutesSeconds,
+	/// `[hh:mm:ss]`
+	#[serde(rename = "hh:mm:ss")]
+	HoursMinutesSeconds,
+}
+
+/// What to do when a recording produces no text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyResultAction {
+	#[default]
+	Notify,
+	Silent,
+	/// Notify with a prompt to record again louder or closer to the microphone
+	RetryLouderHint,
+}
+
+/// Something a hotkey in `action_hotkeys` can trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+	ToggleRecording,
+	ToggleAutoCopy,
+	/// Switch back to the language used before the current one
+	SwapLanguage,
+}
+
+/// Which notifications are shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationLevel {
+	/// Routine notifications such as "Transcribed" as well as errors
+	#[default]
+	All,
+	/// Only errors and warnings
+	Errors,
+	/// No notifications at all
+	None,
+}
+
+/// Acronyms kept in their canonical casing by default, leaving out ones
+/// that are also common words in some language, like "AI" or "IT"
+pub const DEFAULT_ACRONYMS: &[&str] = &[
+	"API", "CLI", "CPU", "CSS", "DNS", "GPU", "HTML", "HTTP", "HTTPS", "JSON", "NASA", "PDF",
+	"SDK", "SQL", "SSH", "URL", "USB", "XML", "YAML",
+];
+
+/// Silence ending a segment in continuous mode when no auto-stop is set
+pub const DEFAULT_SEGMENT_SILENCE_MS: u32 = 1500;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
 	pub hotkey: String,
 	pub language: String,
+	/// Language in use before the current one, for `swap_language`
+	pub previous_language: Option<String>,
 	pub model_path: Option<String>,
+	/// File stem of the selected model, used to find it again if
+	/// `model_path` stops pointing at it
+	pub model_name: Option<String>,
 	pub auto_copy: bool,
 	pub show_notifications: bool,
 	pub use_gpu: bool,
-	pub gpu_device: i32
+	pub gpu_device: i32,
+	/// Preferred whisper-rs GPU backend, "auto" to use whatever is compiled in
+	pub gpu_backend: String,
+	pub start_minimized: bool,
+	pub autostart: bool,
+	pub correction_wordlist: Option<String>,
+	/// Words forced to this exact casing wherever they appear in any case,
+	/// e.g. "api" and "Api" become "API"
+	pub acronyms: Vec<String>,
+	/// Text Whisper is primed with, to steer spelling of uncommon terms
+	pub initial_prompt: Option<String>,
+	/// Term file `initial_prompt` was last built from
+	pub vocabulary_file: Option<String>,
+	pub auto_stop_silence_ms: Option<u32>,
+	pub fallback_models: Vec<String>,
+	pub carry_context: bool,
+	pub min_interval_between_transcriptions_ms: Option<u64>,
+	pub output_mode: OutputMode,
+	/// Sample rate to capture at instead of the device default, if supported
+	pub capture_sample_rate: Option<u32>,
+	pub notification_level: NotificationLevel,
+	/// Play the notification sound, where the platform allows silencing it
+	pub notification_sound: bool,
+	/// File every transcription is appended to, one line each
+	pub transcription_log: Option<String>,
+	/// Format of each logged line, see `text_processing::render_template`
+	pub output_template: String,
+	pub output_format: OutputFormat,
+	pub clipboard_format: ClipboardFormat,
+	/// Directories scanned for models besides the managed models directory
+	pub extra_model_dirs: Vec<String>,
+	/// Put each segment on its own line prefixed with its start time
+	pub inline_timestamps: bool,
+	pub timestamp_format: TimestampFormat,
+	/// Drops segments whose no-speech probability is above this, `None` keeps all
+	pub no_speech_threshold: Option<f32>,
+	pub on_empty_result: EmptyResultAction,
+	/// Fixed boost applied to recordings, 0 leaves them unchanged
+	pub input_gain_db: f32,
+	/// With language "auto", keep using the first detected language
+	pub lock_detected_language: bool,
+	/// Audio host to capture through, e.g. "JACK", `None` uses the platform default
+	pub audio_host: Option<String>,
+	/// Additional hotkeys mapped to the action they trigger
+	pub action_hotkeys: BTreeMap<String, HotkeyAction>,
+	/// Experimental: with language "auto", detect which of `candidate_languages`
+	/// is spoken before transcribing
+	pub multi_language_detection: bool,
+	pub candidate_languages: Vec<String>,
+	/// Abort a transcription after this long, `None` scales with the audio length
+	pub transcription_timeout_secs: Option<u64>,
+	/// Keep the microphone open between recordings so they start instantly
+	pub keep_stream_open: bool,
+	/// Sampling temperature for decoding, 0.0 always picks the likeliest token
+	pub temperature: f32,
+	/// Added to the temperature each time a decode fails Whisper's quality
+	/// checks (e.g. repeating itself), retrying until it reaches 1.0; 0.0
+	/// disables the retries. Any retry above 0.0 samples `best_of`
+	/// candidates instead of running beam search, so beam search only
+	/// applies to the first attempt at temperature 0.0.
+	pub temperature_inc: f32,
+	/// A decode whose token entropy is below this is treated as repetitive
+	/// and retried at a higher temperature
+	pub entropy_threshold: f32,
+	/// A decode whose average token log probability is below this is retried
+	/// at a higher temperature
+	pub logprob_threshold: f32,
+	/// Flag results whose word count is more than this many times their
+	/// distinct words as a possible hallucination, `None` to disable
+	pub repetition_warning_ratio: Option<f32>,
+	/// End each transcription with a period unless it already ends with
+	/// punctuation
+	pub auto_end_punctuation: bool,
+	/// Application or window name focused by the `target_app` output mode
+	pub target_app: Option<String>,
+	/// Wait before the `type` output mode starts typing, for windows that
+	/// drop the first characters right after taking focus
+	pub type_start_delay_ms: u64,
+	/// Pause after each typed character, for apps that can't keep up
+	pub type_char_delay_ms: u64,
+	/// Emit downsampled audio as `pcm-frame` events while recording
+	pub stream_pcm: bool,
+	/// Suppress every notification and sound regardless of the other
+	/// notification settings, leaving only the text output
+	pub silent_mode: bool,
+	/// Frames per audio callback, `None` lets the OS choose. Smaller buffers
+	/// lower latency, larger ones avoid dropouts.
+	pub audio_buffer_frames: Option<u32>,
+	/// Program run after each successful transcription, with `{text}` in its
+	/// arguments replaced by the text, or the text on stdin without one.
+	/// It runs with the user's full permissions, so only point it at
+	/// programs you trust with anything you might dictate.
+	pub post_command: Option<String>,
+	/// With inline timestamps, mark pauses of at least this many
+	/// milliseconds with a `---` line as a likely change of speaker
+	pub speaker_gap_ms: Option<u32>,
+	/// Keep each recording as a WAV file in the recordings directory
+	pub save_recordings: bool,
+	/// Older saved recordings beyond this many are deleted
+	pub max_saved_recordings: Option<usize>,
+	/// Decoding threads, `None` uses one per core up to 8
+	pub n_threads: Option<i32>,
+	/// Overrides for individual models, keyed by model name
+	pub model_settings: BTreeMap<String, ModelSettings>,
+	/// Prefix each transcription with its language code, e.g. "[it] ..."
+	pub tag_language: bool,
+	/// Input device names in order of preference, the first one connected
+	/// is used and the system default when none are
+	pub preferred_devices: Vec<String>,
+	/// Input devices that capture system audio, for ones not recognized
+	/// by name. They are captured at their own rate and channel count.
+	pub loopback_devices: Vec<String>,
+	/// Switch to `low_power_model` while running on battery
+	pub adaptive_power: bool,
+	pub low_power_model: Option<String>,
+	/// Transcriptions longer than this many characters are shown for
+	/// review before they reach the clipboard
+	pub confirm_above_chars: Option<usize>,
+	/// How long a transcription waits for review before it's discarded
+	pub review_timeout_secs: u64,
+	/// Also emit each sentence of a transcription on its own
+	pub split_sentences: bool,
+	/// Pause between the sentences emitted by `split_sentences`
+	pub sentence_delay_ms: u64,
+	/// Give focus back to the window that had it when the main window
+	/// is hidden again
+	pub restore_previous_focus: bool,
+	/// Keep recording after each transcription until the hotkey is pressed
+	/// again, with silence ending each segment
+	pub continuous_mode: bool,
+	/// Added after each continuous mode segment that another one follows,
+	/// e.g. a space or a newline
+	pub segment_separator: Option<String>,
 }
 
 impl Default for Config {
@@ -19,20 +255,110 @@ impl Default for Config {
 		Self {
 			hotkey: "F9".to_string(),
 			language: "auto".to_string(),
+			previous_language: None,
 			model_path: None,
+			model_name: None,
 			auto_copy: true,
 			show_notifications: true,
 			use_gpu: true,
-			gpu_device: 0
+			gpu_device: 0,
+			gpu_backend: "auto".to_string(),
+			start_minimized: false,
+			autostart: false,
+			correction_wordlist: None,
+			acronyms: DEFAULT_ACRONYMS.iter().map(|a| a.to_string()).collect(),
+			initial_prompt: None,
+			vocabulary_file: None,
+			auto_stop_silence_ms: None,
+			fallback_models: Vec::new(),
+			carry_context: false,
+			min_interval_between_transcriptions_ms: None,
+			output_mode: OutputMode::Clipboard,
+			capture_sample_rate: None,
+			notification_level: NotificationLevel::All,
+			notification_sound: true,
+			transcription_log: None,
+			output_template: "{time} {text}".to_string(),
+			output_format: OutputFormat::Plain,
+			clipboard_format: ClipboardFormat::Plain,
+			extra_model_dirs: Vec::new(),
+			inline_timestamps: false,
+			timestamp_format: TimestampFormat::MinutesSeconds,
+			// High enough that quiet but real speech is kept
+			no_speech_threshold: Some(0.9),
+			on_empty_result: EmptyResultAction::Notify,
+			input_gain_db: 0.0,
+			lock_detected_language: false,
+			audio_host: None,
+			action_hotkeys: BTreeMap::new(),
+			multi_language_detection: false,
+			candidate_languages: Vec::new(),
+			transcription_timeout_secs: None,
+			keep_stream_open: false,
+			temperature: 0.0,
+			// whisper.cpp's own default
+			temperature_inc: 0.2,
+			entropy_threshold: 2.4,
+			logprob_threshold: -1.0,
+			repetition_warning_ratio: Some(3.0),
+			auto_end_punctuation: false,
+			target_app: None,
+			type_start_delay_ms: 0,
+			type_char_delay_ms: 0,
+			stream_pcm: false,
+			silent_mode: false,
+			audio_buffer_frames: None,
+			post_command: None,
+			speaker_gap_ms: None,
+			save_recordings: false,
+			max_saved_recordings: None,
+			n_threads: None,
+			model_settings: BTreeMap::new(),
+			tag_language: false,
+			preferred_devices: Vec::new(),
+			loopback_devices: Vec::new(),
+			adaptive_power: false,
+			low_power_model: None,
+			confirm_above_chars: None,
+			review_timeout_secs: 120,
+			split_sentences: false,
+			sentence_delay_ms: 0,
+			restore_previous_focus: true,
+			continuous_mode: false,
+			segment_separator: None,
 		}
 	}
 }
 
+/// Whisper language code for an OS locale such as "it-IT", "pt_BR.UTF-8"
+/// or "zh-Hans-CN", if `is_supported` accepts it
+pub fn language_from_locale(locale: &str, is_supported: impl Fn(&str) -> bool) -> Option<String> {
+	let code = locale
+		.split(['-', '_', '.', '@'])
+		.next()?
+		.to_lowercase();
+	// Locale codes Whisper knows under another name
+	let code = match code.as_str() {
+		"nb" | "nn" => "no".to_string(),
+		"iw" => "he".to_string(),
+		"jv" => "jw".to_string(),
+		_ => code,
+	};
+
+	is_supported(&code).then_some(code)
+}
+
+/// Environment variable that overrides the config directory
+pub const CONFIG_DIR_ENV: &str = "SHY_TO_TEXT_CONFIG_DIR";
+
 impl Config {
 	pub fn config_dir() -> Result<PathBuf> {
-		let config_dir = dirs::config_dir()
-			.context("Failed to get config directory")?
-			.join("shy-to-text");
+		let config_dir = match std::env::var_os(CONFIG_DIR_ENV) {
+			Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+			_ => dirs::config_dir()
+				.context("Failed to get config directory")?
+				.join("shy-to-text"),
+		};
 
 		if !config_dir.exists() {
 			fs::create_dir_all(&config_dir).context("Failed to create config directory")?;
@@ -51,39 +377,354 @@ impl Config {
 		Ok(models_dir)
 	}
 
+	pub fn recordings_dir() -> Result<PathBuf> {
+		let recordings_dir = Self::config_dir()?.join("recordings");
+
+		if !recordings_dir.exists() {
+			fs::create_dir_all(&recordings_dir).context("Failed to create recordings directory")?;
+		}
+
+		Ok(recordings_dir)
+	}
+
 	pub fn config_path() -> Result<PathBuf> {
 		Ok(Self::config_dir()?.join("config.json"))
 	}
 
-	pub fn load() -> Result<Self> {
-		let config_path = Self::config_path()?;
+	/// Loads the config, creating it on first run with the language of the
+	/// OS locale when `is_supported_language` accepts it
+	pub fn load(is_supported_language: impl Fn(&str) -> bool) -> Result<Self> {
+		let locale = sys_locale::get_locale();
+		Self::load_from(&Self::config_path()?, locale.as_deref(), is_supported_language)
+	}
+
+	/// Defaults for a fresh install, with the language set from `locale`
+	fn first_run(locale: Option<&str>, is_supported_language: impl Fn(&str) -> bool) -> Self {
+		eprintln!("Detected locale: {}", locale.unwrap_or("unknown"));
+
+		let language = locale.and_then(|locale| language_from_locale(locale, is_supported_language));
+		let mut config = Self::default();
+		if let Some(language) = language {
+			eprintln!("Setting the transcription language to {}", language);
+			config.language = language;
+		}
+		config
+	}
+
+	/// Loads the config at `path`, creating it with the first run defaults
+	/// for `locale` if missing. A malformed file is moved to `<path>.bak`
+	/// and replaced with plain defaults, keeping "auto" as the language.
+	fn load_from(
+		path: &Path,
+		locale: Option<&str>,
+		is_supported_language: impl Fn(&str) -> bool,
+	) -> Result<Self> {
+		if !path.exists() {
+			let config = Config::first_run(locale, is_supported_language);
+			config.save_to(path)?;
+			return Ok(config);
+		}
+
+		let content = fs::read_to_string(path).context("Failed to read config file")?;
+		match serde_json::from_str::<Config>(&content) {
+			Ok(mut config) => {
+				config.clamp_invalid();
+				Ok(config)
+			}
+			Err(e) => {
+				let mut backup = path.as_os_str().to_owned();
+				backup.push(".bak");
+				let backup = PathBuf::from(backup);
+
+				eprintln!(
+					"Failed to parse config file: {}, backing it up to {}",
+					e,
+					backup.display()
+				);
+				fs::rename(path, &backup).context("Failed to back up config file")?;
+
+				let config = Config::default();
+				config.save_to(path)?;
+				Ok(config)
+			}
+		}
+	}
+
+	/// Which notifications are shown, none while `silent_mode` is on
+	pub fn effective_notification_level(&self) -> NotificationLevel {
+		if self.silent_mode {
+			NotificationLevel::None
+		} else {
+			self.notification_level
+		}
+	}
+
+	/// Thread count for the model at `model_path`: its own override, then
+	/// the global `n_threads`, `None` to pick automatically
+	pub fn threads_for_model(&self, model_path: &str) -> Option<i32> {
+		model_threads(&self.model_settings, self.n_threads, model_path)
+	}
 
-		if config_path.exists() {
-			let content = fs::read_to_string(&config_path).context("Failed to read config file")?;
-			let config: Config =
-				serde_json::from_str(&content).context("Failed to parse config file")?;
-			Ok(config)
+	/// Model that should be loaded for the given power source, `None` when
+	/// adaptive power is off or no low-power model is configured
+	pub fn model_for_power(&self, on_battery: bool) -> Option<&str> {
+		if !self.adaptive_power {
+			return None;
+		}
+		let low_power = self.low_power_model.as_deref()?;
+		if on_battery {
+			Some(low_power)
 		} else {
-			let config = Config::default();
-			config.save()?;
-			Ok(config)
+			self.model_path.as_deref()
+		}
+	}
+
+	/// Whether `text` is long enough to need a review before it's copied
+	pub fn needs_review(&self, text: &str) -> bool {
+		self.confirm_above_chars
+			.is_some_and(|limit| text.chars().count() > limit)
+	}
+
+	/// Switches to `previous_language`, remembering the current one in its
+	/// place. Returns false when there's no other language to switch to.
+	pub fn swap_language(&mut self) -> bool {
+		match self.previous_language.take() {
+			Some(previous) if previous != self.language => {
+				self.previous_language = Some(std::mem::replace(&mut self.language, previous));
+				true
+			}
+			previous => {
+				self.previous_language = previous;
+				false
+			}
+		}
+	}
+
+	/// Silence that ends a recording. Continuous mode depends on it to end
+	/// segments, so it falls back to a default there.
+	pub fn segment_silence_ms(&self) -> Option<u32> {
+		self.auto_stop_silence_ms
+			.or(self.continuous_mode.then_some(DEFAULT_SEGMENT_SILENCE_MS))
+	}
+
+	/// Returns a copy with a JSON merge patch (RFC 7386) applied: objects
+	/// are merged recursively and `null` resets a field to its default.
+	/// Fields this version doesn't know are rejected.
+	pub fn apply_patch(&self, patch: &serde_json::Value) -> Result<Config> {
+		let serde_json::Value::Object(fields) = patch else {
+			anyhow::bail!("Config patch must be an object");
+		};
+
+		let mut current = serde_json::to_value(self)?;
+		let current_fields = current
+			.as_object()
+			.ok_or_else(|| anyhow::anyhow!("Config didn't serialize to an object"))?;
+		if let Some(unknown) = fields.keys().find(|key| !current_fields.contains_key(*key)) {
+			anyhow::bail!("Unknown config field: {}", unknown);
+		}
+
+		merge_patch(&mut current, patch);
+		serde_json::from_value(current).context("Invalid config patch")
+	}
+
+	/// Rejects values Whisper can't use
+	pub fn validate(&self) -> Result<()> {
+		if !(0.0..=1.0).contains(&self.temperature) {
+			anyhow::bail!("Temperature must be between 0 and 1, got {}", self.temperature);
+		}
+		if !(0.0..=1.0).contains(&self.temperature_inc) {
+			anyhow::bail!(
+				"Temperature increment must be between 0 and 1, got {}",
+				self.temperature_inc
+			);
+		}
+		Ok(())
+	}
+
+	/// Brings values `validate` would reject back into range, for configs
+	/// edited by hand or written by an older version
+	fn clamp_invalid(&mut self) {
+		let defaults = Config::default();
+		let fields = [
+			("temperature", &mut self.temperature, defaults.temperature),
+			("temperature_inc", &mut self.temperature_inc, defaults.temperature_inc),
+		];
+		for (name, value, default) in fields {
+			if !(0.0..=1.0).contains(value) {
+				let clamped = if value.is_nan() { default } else { value.clamp(0.0, 1.0) };
+				eprintln!("Config {} of {} is out of range, using {}", name, value, clamped);
+				*value = clamped;
+			}
 		}
 	}
 
 	pub fn save(&self) -> Result<()> {
-		let config_path = Self::config_path()?;
+		self.save_to(&Self::config_path()?)
+	}
+
+	fn save_to(&self, path: &Path) -> Result<()> {
 		let content =
 			serde_json::to_string_pretty(self).context("Failed to serialize config")?;
-		fs::write(&config_path, content).context("Failed to write config file")?;
+		fs::write(path, content).context("Failed to write config file")?;
 		Ok(())
 	}
 
-	pub fn detect_models() -> Result<Vec<ModelInfo>> {
-		let models_dir = Self::models_dir()?;
+	/// How long a transcription of `audio_secs` may run before it is aborted.
+	/// The default allows far more than even CPU decoding of large models needs.
+	pub fn transcription_timeout(&self, audio_secs: f64) -> Duration {
+		match self.transcription_timeout_secs {
+			Some(secs) => Duration::from_secs(secs),
+			None => Duration::from_secs(60) + Duration::from_secs_f64(audio_secs.max(0.0) * 10.0),
+		}
+	}
+
+	pub fn profiles_dir() -> Result<PathBuf> {
+		let profiles_dir = Self::config_dir()?.join("profiles");
+
+		if !profiles_dir.exists() {
+			fs::create_dir_all(&profiles_dir).context("Failed to create profiles directory")?;
+		}
+
+		Ok(profiles_dir)
+	}
+
+	/// Saves this config as a new named profile, failing if it already exists
+	pub fn save_as_profile(&self, name: &str) -> Result<()> {
+		validate_profile_name(name)?;
+		let path = Self::profiles_dir()?.join(format!("{}.json", name));
+		self.save_new_to(&path)
+			.with_context(|| format!("Failed to create profile \"{}\"", name))
+	}
+
+	fn save_new_to(&self, path: &Path) -> Result<()> {
+		let content =
+			serde_json::to_string_pretty(self).context("Failed to serialize config")?;
+		let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+			Ok(file) => file,
+			Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+				anyhow::bail!("A profile with this name already exists")
+			}
+			Err(e) => return Err(e.into()),
+		};
+		file.write_all(content.as_bytes())?;
+		Ok(())
+	}
+
+	/// Resolves the correction wordlist path, relative paths are looked up
+	/// inside the config directory.
+	pub fn correction_wordlist_path(&self) -> Result<Option<PathBuf>> {
+		Self::resolve_in_config_dir(self.correction_wordlist.as_deref())
+	}
+
+	/// Replaces every file or directory path in the config with
+	/// `map(path)`. Path fields are listed here so a new one isn't missed
+	/// wherever paths must not leak, like diagnostics.
+	pub fn map_paths(&mut self, map: impl Fn(&str) -> String) {
+		let single = [
+			&mut self.model_path,
+			&mut self.low_power_model,
+			&mut self.correction_wordlist,
+			&mut self.vocabulary_file,
+			&mut self.transcription_log,
+		];
+		for path in single.into_iter().flatten() {
+			*path = map(path);
+		}
+		for path in self.fallback_models.iter_mut().chain(&mut self.extra_model_dirs) {
+			*path = map(path);
+		}
+	}
+
+	/// Resolves the transcription log path the same way as the wordlist
+	pub fn transcription_log_path(&self) -> Result<Option<PathBuf>> {
+		Self::resolve_in_config_dir(self.transcription_log.as_deref())
+	}
+
+	fn resolve_in_config_dir(path: Option<&str>) -> Result<Option<PathBuf>> {
+		match path {
+			Some(path) => {
+				let path = PathBuf::from(path);
+				if path.is_absolute() {
+					Ok(Some(path))
+				} else {
+					Ok(Some(Self::config_dir()?.join(path)))
+				}
+			}
+			None => Ok(None),
+		}
+	}
+
+	/// Lists models in the managed models directory and in `extra_model_dirs`
+	pub fn detect_models(&self) -> Result<Vec<ModelInfo>> {
+		Ok(Self::detect_models_from(&self.model_dirs()?))
+	}
+
+	fn model_dirs(&self) -> Result<Vec<PathBuf>> {
+		let mut dirs = vec![Self::models_dir()?];
+		dirs.extend(self.extra_model_dirs.iter().map(PathBuf::from));
+		Ok(dirs)
+	}
+
+	/// Path of the configured model: `model_path` while the file is there,
+	/// otherwise the first model named `model_name` in the known model
+	/// directories. `None` when no model is configured or it can't be found.
+	pub fn resolve_model_path(&self) -> Option<String> {
+		let dirs = self.model_dirs().unwrap_or_default();
+		self.resolve_model_path_in(&dirs)
+	}
+
+	fn resolve_model_path_in(&self, dirs: &[PathBuf]) -> Option<String> {
+		if let Some(ref path) = self.model_path
+			&& Path::new(path).is_file()
+		{
+			return Some(path.clone());
+		}
+
+		let name = self.model_name.as_deref()?;
+		Self::detect_models_from(dirs)
+			.into_iter()
+			.find(|model| model.name == name)
+			.map(|model| model.path)
+	}
+
+	/// Name stored alongside a model path so it can be found after a move
+	pub fn model_name_for(path: &str) -> Option<String> {
+		Path::new(path)
+			.file_stem()
+			.map(|stem| stem.to_string_lossy().to_string())
+	}
+
+	/// Scans each directory in order, skipping unreadable ones and models
+	/// already found through another path.
+	fn detect_models_from(dirs: &[PathBuf]) -> Vec<ModelInfo> {
+		let mut models: Vec<ModelInfo> = Vec::new();
+		let mut seen = HashSet::new();
+
+		for dir in dirs {
+			let found = match Self::detect_models_in(dir) {
+				Ok(found) => found,
+				Err(e) => {
+					eprintln!("Failed to scan models in {}: {}", dir.display(), e);
+					continue;
+				}
+			};
+
+			for model in found {
+				let key = fs::canonicalize(&model.path).unwrap_or_else(|_| PathBuf::from(&model.path));
+				if seen.insert(key) {
+					models.push(model);
+				}
+			}
+		}
+
+		models
+	}
+
+	fn detect_models_in(models_dir: &Path) -> Result<Vec<ModelInfo>> {
 		let mut models = Vec::new();
 
 		if models_dir.exists() {
-			for entry in fs::read_dir(&models_dir)? {
+			for entry in fs::read_dir(models_dir)? {
 				let entry = entry?;
 				let path = entry.path();
 				if path.extension().map_or(false, |ext| ext == "bin") {
@@ -92,6 +733,8 @@ impl Config {
 							name: name.to_string_lossy().to_string(),
 							path: path.to_string_lossy().to_string(),
 							size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+							source_dir: models_dir.to_string_lossy().to_string(),
+							metadata: ModelMetadata::read(&path).ok(),
 						});
 					}
 				}
@@ -107,4 +750,619 @@ pub struct ModelInfo {
 	pub name: String,
 	pub path: String,
 	pub size: u64,
+	/// Directory the model was found in
+	pub source_dir: String,
+	/// Read from the file header, `None` when it isn't a valid ggml model
+	pub metadata: Option<ModelMetadata>,
+}
+
+/// Profile names become file names, so only a safe subset of characters is allowed
+fn validate_profile_name(name: &str) -> Result<()> {
+	let valid = !name.trim().is_empty()
+		&& name.len() <= 64
+		&& name
+			.chars()
+			.all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' '));
+
+	if !valid {
+		anyhow::bail!(
+			"Invalid profile name \"{}\": use letters, numbers, spaces, '-' or '_'",
+			name
+		);
+	}
+	Ok(())
+}
+
+/// Magic at the start of every ggml model file, "ggml" read as a little-endian u32
+const GGML_MAGIC: u32 = 0x6767_6d6c;
+
+/// Vocabulary size of the English-only models, multilingual ones are larger
+const ENGLISH_VOCAB_SIZE: i32 = 51864;
+
+/// Settings that apply to one model only
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelSettings {
+	/// Decoding threads for this model, small models often run faster
+	/// with fewer
+	pub n_threads: Option<i32>,
+}
+
+/// Thread count for the model at `model_path`: its override in
+/// `model_settings`, then `n_threads`, `None` to pick automatically
+pub fn model_threads(
+	model_settings: &BTreeMap<String, ModelSettings>,
+	n_threads: Option<i32>,
+	model_path: &str,
+) -> Option<i32> {
+	Config::model_name_for(model_path)
+		.and_then(|name| model_settings.get(&name))
+		.and_then(|settings| settings.n_threads)
+		.or(n_threads)
+		.filter(|&threads| threads > 0)
+}
+
+/// Model properties read from the hyperparameters in a ggml file header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelMetadata {
+	/// Size class such as "base" or "large", derived from the layer count
+	pub model_type: String,
+	pub multilingual: bool,
+	pub n_vocab: i32,
+	pub n_mels: i32,
+	pub n_audio_layer: i32,
+	pub n_text_layer: i32,
+	/// Weight type such as "f16" or "q8_0"
+	pub quantization: String,
+}
+
+/// Result of checking a model file without loading it
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelValidation {
+	pub path: String,
+	pub ok: bool,
+	pub error: Option<String>,
+}
+
+impl ModelValidation {
+	pub fn check(path: &str) -> Self {
+		let result = ModelMetadata::read(Path::new(path)).and_then(|metadata| metadata.check_supported());
+		Self {
+			path: path.to_string(),
+			ok: result.is_ok(),
+			error: result.err().map(|e| format!("{:#}", e)),
+		}
+	}
+}
+
+impl ModelMetadata {
+	/// Rejects models whose weight type this build of whisper.cpp can't load
+	pub fn check_supported(&self) -> Result<()> {
+		if self.quantization == "unknown" {
+			anyhow::bail!("Unsupported weight type");
+		}
+		Ok(())
+	}
+
+	/// Reads only the magic and hyperparameters at the start of the file
+	pub fn read(path: &Path) -> Result<Self> {
+		let mut file =
+			fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+		// Magic followed by 11 i32 hyperparameters
+		let mut header = [0u8; 48];
+		file.read_exact(&mut header)
+			.with_context(|| format!("{} is too small to be a model", path.display()))?;
+		Self::parse(&header).with_context(|| format!("Invalid model file: {}", path.display()))
+	}
+
+	fn parse(header: &[u8; 48]) -> Result<Self> {
+		let field = |i: usize| i32::from_le_bytes(header[i * 4..i * 4 + 4].try_into().unwrap());
+
+		if field(0) as u32 != GGML_MAGIC {
+			anyhow::bail!("Missing ggml magic");
+		}
+
+		let n_vocab = field(1);
+		let n_audio_layer = field(5);
+		let n_text_layer = field(9);
+		let n_mels = field(10);
+		// Newer files encode the quantization version as ftype / 1000
+		let ftype = field(11) % 1000;
+
+		if n_vocab <= 0 || n_audio_layer <= 0 || n_mels <= 0 {
+			anyhow::bail!("Implausible hyperparameters");
+		}
+
+		let model_type = match n_audio_layer {
+			4 => "tiny",
+			6 => "base",
+			12 => "small",
+			24 => "medium",
+			32 => "large",
+			_ => "unknown",
+		};
+
+		let quantization = match ftype {
+			0 => "f32",
+			1 => "f16",
+			2 => "q4_0",
+			3 => "q4_1",
+			7 => "q8_0",
+			8 => "q5_0",
+			9 => "q5_1",
+			10 => "q2_k",
+			11 => "q3_k",
+			12 => "q4_k",
+			13 => "q5_k",
+			14 => "q6_k",
+			_ => "unknown",
+		};
+
+		Ok(Self {
+			model_type: model_type.to_string(),
+			multilingual: n_vocab > ENGLISH_VOCAB_SIZE,
+			n_vocab,
+			n_mels,
+			n_audio_layer,
+			n_text_layer,
+			quantization: quantization.to_string(),
+		})
+	}
+}
+
+fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+	let serde_json::Value::Object(patch_fields) = patch else {
+		*target = patch.clone();
+		return;
+	};
+	if !target.is_object() {
+		*target = serde_json::Value::Object(serde_json::Map::new());
+	}
+	let Some(target_fields) = target.as_object_mut() else {
+		return;
+	};
+
+	for (key, value) in patch_fields {
+		if value.is_null() {
+			target_fields.remove(key);
+		} else {
+			merge_patch(target_fields.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_load_corrupt_config_creates_backup_and_returns_defaults() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("config.json");
+		let broken = r#"{ "hotkey": "F8", "language": "#;
+		fs::write(&path, broken).unwrap();
+
+		let config = Config::load_from(&path, None, |_| false).unwrap();
+
+		let backup = dir.path().join("config.json.bak");
+		assert!(backup.exists(), "Corrupt config should be backed up");
+		assert_eq!(fs::read_to_string(&backup).unwrap(), broken);
+		assert_eq!(config.hotkey, Config::default().hotkey);
+		assert_eq!(config.language, Config::default().language);
+	}
+
+	#[test]
+	fn test_config_dir_env_override() {
+		let dir = tempfile::tempdir().unwrap();
+		let config_dir = dir.path().join("portable");
+
+		// SAFETY: no other test reads or writes this variable
+		unsafe { std::env::set_var(CONFIG_DIR_ENV, &config_dir) };
+		let path = Config::config_path();
+		unsafe { std::env::remove_var(CONFIG_DIR_ENV) };
+
+		assert_eq!(path.unwrap(), config_dir.join("config.json"));
+		assert!(config_dir.is_dir(), "The directory should be created");
+	}
+
+	#[test]
+	fn test_first_run_takes_language_from_locale() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("config.json");
+		let supported = |code: &str| code == "it";
+
+		let config = Config::load_from(&path, Some("it-IT"), supported).unwrap();
+		assert_eq!(config.language, "it");
+
+		// An existing choice is never overridden
+		Config::default().save_to(&path).unwrap();
+		let config = Config::load_from(&path, Some("it-IT"), supported).unwrap();
+		assert_eq!(config.language, "auto");
+
+		let other = dir.path().join("other.json");
+		let config = Config::load_from(&other, Some("sw-KE"), supported).unwrap();
+		assert_eq!(config.language, "auto");
+	}
+
+	#[test]
+	fn test_language_from_locale() {
+		let supported = |code: &str| ["en", "it", "pt", "zh", "no"].contains(&code);
+		assert_eq!(language_from_locale("it-IT", supported).as_deref(), Some("it"));
+		assert_eq!(language_from_locale("pt_BR.UTF-8", supported).as_deref(), Some("pt"));
+		assert_eq!(language_from_locale("zh-Hans-CN", supported).as_deref(), Some("zh"));
+		assert_eq!(language_from_locale("nb-NO", supported).as_deref(), Some("no"));
+		assert_eq!(language_from_locale("C", supported), None);
+		assert_eq!(language_from_locale("xx-YY", supported), None);
+	}
+
+	#[test]
+	fn test_load_missing_config_writes_defaults() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("config.json");
+
+		let config = Config::load_from(&path, None, |_| false).unwrap();
+
+		assert!(path.exists());
+		assert_eq!(config.hotkey, "F9");
+	}
+
+	#[test]
+	fn test_save_and_load_roundtrip() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("config.json");
+
+		let config = Config {
+			hotkey: "Ctrl+Shift+R".to_string(),
+			fallback_models: vec!["base.bin".to_string()],
+			capture_sample_rate: Some(16000),
+			..Config::default()
+		};
+		config.save_to(&path).unwrap();
+
+		let loaded = Config::load_from(&path, None, |_| false).unwrap();
+		assert_eq!(loaded.hotkey, "Ctrl+Shift+R");
+		assert_eq!(loaded.fallback_models, vec!["base.bin".to_string()]);
+		assert_eq!(loaded.capture_sample_rate, Some(16000));
+	}
+
+	#[test]
+	fn test_load_clamps_out_of_range_values() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("config.json");
+		Config {
+			temperature: 5.0,
+			temperature_inc: -0.5,
+			..Config::default()
+		}
+		.save_to(&path)
+		.unwrap();
+
+		let loaded = Config::load_from(&path, None, |_| false).unwrap();
+		assert_eq!(loaded.temperature, 1.0);
+		assert_eq!(loaded.temperature_inc, 0.0);
+		assert!(loaded.validate().is_ok());
+	}
+
+	#[test]
+	fn test_detect_models_only_lists_bin_files() {
+		let dir = tempfile::tempdir().unwrap();
+		fs::write(dir.path().join("ggml-small.bin"), [0u8; 4]).unwrap();
+		fs::write(dir.path().join("notes.txt"), "not a model").unwrap();
+
+		let models = Config::detect_models_in(dir.path()).unwrap();
+
+		assert_eq!(models.len(), 1);
+		assert_eq!(models[0].name, "ggml-small");
+		assert_eq!(models[0].size, 4);
+	}
+
+	#[test]
+	fn test_detect_models_from_dedupes_and_tags_source() {
+		let managed = tempfile::tempdir().unwrap();
+		let external = tempfile::tempdir().unwrap();
+		fs::write(managed.path().join("ggml-base.bin"), []).unwrap();
+		fs::write(external.path().join("ggml-large.bin"), []).unwrap();
+
+		let dirs = vec![
+			managed.path().to_path_buf(),
+			external.path().to_path_buf(),
+			managed.path().join("."),
+			external.path().join("missing"),
+		];
+		let models = Config::detect_models_from(&dirs);
+
+		assert_eq!(models.len(), 2);
+		assert_eq!(models[0].name, "ggml-base");
+		assert_eq!(models[0].source_dir, managed.path().to_string_lossy());
+		assert_eq!(models[1].name, "ggml-large");
+		assert_eq!(models[1].source_dir, external.path().to_string_lossy());
+	}
+
+	fn model_header(n_vocab: i32, n_audio_layer: i32, n_mels: i32, ftype: i32) -> [u8; 48] {
+		let fields = [
+			GGML_MAGIC as i32,
+			n_vocab,
+			1500,
+			512,
+			8,
+			n_audio_layer,
+			448,
+			512,
+			8,
+			n_audio_layer,
+			n_mels,
+			ftype,
+		];
+		let mut header = [0u8; 48];
+		for (i, value) in fields.iter().enumerate() {
+			header[i * 4..i * 4 + 4].copy_from_slice(&value.to_le_bytes());
+		}
+		header
+	}
+
+	#[test]
+	fn test_model_metadata_parse() {
+		let metadata = ModelMetadata::parse(&model_header(51865, 12, 80, 2007)).unwrap();
+		assert_eq!(metadata.model_type, "small");
+		assert_eq!(metadata.quantization, "q8_0");
+		assert!(metadata.multilingual);
+
+		let metadata = ModelMetadata::parse(&model_header(51864, 6, 80, 1)).unwrap();
+		assert_eq!(metadata.model_type, "base");
+		assert_eq!(metadata.quantization, "f16");
+		assert!(!metadata.multilingual);
+	}
+
+	#[test]
+	fn test_model_metadata_rejects_non_models() {
+		let mut header = model_header(51865, 12, 80, 1);
+		header[..4].copy_from_slice(b"<!DO");
+		assert!(ModelMetadata::parse(&header).is_err());
+
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("short.bin");
+		fs::write(&path, &header[..10]).unwrap();
+		assert!(ModelMetadata::read(&path).is_err());
+	}
+
+	#[test]
+	fn test_model_validation_reports_errors() {
+		let dir = tempfile::tempdir().unwrap();
+
+		let good = dir.path().join("good.bin");
+		fs::write(&good, model_header(51865, 6, 80, 1)).unwrap();
+		let validation = ModelValidation::check(&good.to_string_lossy());
+		assert!(validation.ok);
+		assert!(validation.error.is_none());
+
+		let odd = dir.path().join("odd.bin");
+		fs::write(&odd, model_header(51865, 6, 80, 42)).unwrap();
+		let validation = ModelValidation::check(&odd.to_string_lossy());
+		assert!(!validation.ok);
+		assert_eq!(validation.error.as_deref(), Some("Unsupported weight type"));
+
+		let missing = ModelValidation::check(&dir.path().join("missing.bin").to_string_lossy());
+		assert!(!missing.ok);
+	}
+
+	#[test]
+	fn test_resolve_model_path_finds_moved_model() {
+		let old_dir = tempfile::tempdir().unwrap();
+		let new_dir = tempfile::tempdir().unwrap();
+		let moved = new_dir.path().join("ggml-base.bin");
+		fs::write(&moved, b"").unwrap();
+
+		let config = Config {
+			model_path: Some(old_dir.path().join("ggml-base.bin").to_string_lossy().to_string()),
+			model_name: Some("ggml-base".to_string()),
+			..Config::default()
+		};
+		let dirs = [new_dir.path().to_path_buf()];
+		assert_eq!(
+			config.resolve_model_path_in(&dirs),
+			Some(moved.to_string_lossy().to_string())
+		);
+
+		let unnamed = Config {
+			model_name: None,
+			..config.clone()
+		};
+		assert_eq!(unnamed.resolve_model_path_in(&dirs), None);
+
+		let in_place = Config {
+			model_path: Some(moved.to_string_lossy().to_string()),
+			model_name: Some("other".to_string()),
+			..Config::default()
+		};
+		assert_eq!(
+			in_place.resolve_model_path_in(&[]),
+			Some(moved.to_string_lossy().to_string())
+		);
+	}
+
+	#[test]
+	fn test_silent_mode_overrides_notification_level() {
+		let config = Config {
+			notification_level: NotificationLevel::All,
+			..Config::default()
+		};
+		assert_eq!(config.effective_notification_level(), NotificationLevel::All);
+
+		let config = Config {
+			silent_mode: true,
+			..config
+		};
+		assert_eq!(config.effective_notification_level(), NotificationLevel::None);
+	}
+
+	#[test]
+	fn test_threads_for_model_prefers_model_override() {
+		let mut config = Config::default();
+		assert_eq!(config.threads_for_model("/models/ggml-tiny.bin"), None);
+
+		config.n_threads = Some(6);
+		config.model_settings.insert(
+			"ggml-tiny".to_string(),
+			ModelSettings { n_threads: Some(2) },
+		);
+		assert_eq!(config.threads_for_model("/models/ggml-tiny.bin"), Some(2));
+		assert_eq!(config.threads_for_model("/models/ggml-large-v3.bin"), Some(6));
+	}
+
+	#[test]
+	fn test_model_for_power() {
+		let mut config = Config {
+			model_path: Some("/models/ggml-large-v3.bin".to_string()),
+			low_power_model: Some("/models/ggml-tiny.bin".to_string()),
+			..Config::default()
+		};
+		assert_eq!(config.model_for_power(true), None);
+
+		config.adaptive_power = true;
+		assert_eq!(config.model_for_power(true), Some("/models/ggml-tiny.bin"));
+		assert_eq!(config.model_for_power(false), Some("/models/ggml-large-v3.bin"));
+
+		config.low_power_model = None;
+		assert_eq!(config.model_for_power(true), None);
+	}
+
+	#[test]
+	fn test_map_paths_covers_every_path_field() {
+		let mut config = Config {
+			model_path: Some("/home/me/models/a.bin".to_string()),
+			low_power_model: Some("/home/me/models/b.bin".to_string()),
+			correction_wordlist: Some("/home/me/words.txt".to_string()),
+			vocabulary_file: Some("/home/me/vocab.txt".to_string()),
+			transcription_log: Some("/home/me/log.md".to_string()),
+			fallback_models: vec!["/home/me/models/c.bin".to_string()],
+			extra_model_dirs: vec!["/home/me/more".to_string()],
+			..Config::default()
+		};
+		config.map_paths(|_| "<path>".to_string());
+
+		let json = serde_json::to_string(&config).unwrap();
+		assert!(!json.contains("/home/me"), "{}", json);
+	}
+
+	#[test]
+	fn test_needs_review_counts_characters() {
+		let mut config = Config::default();
+		assert!(!config.needs_review(&"a".repeat(10_000)));
+
+		config.confirm_above_chars = Some(5);
+		assert!(!config.needs_review("àèìòù"));
+		assert!(config.needs_review("àèìòù!"));
+	}
+
+	#[test]
+	fn test_swap_language_flips_back_and_forth() {
+		let mut config = Config {
+			language: "en".to_string(),
+			..Config::default()
+		};
+		assert!(!config.swap_language());
+		assert_eq!(config.language, "en");
+
+		config.previous_language = Some("it".to_string());
+		assert!(config.swap_language());
+		assert_eq!(config.language, "it");
+		assert_eq!(config.previous_language.as_deref(), Some("en"));
+
+		assert!(config.swap_language());
+		assert_eq!(config.language, "en");
+		assert_eq!(config.previous_language.as_deref(), Some("it"));
+	}
+
+	#[test]
+	fn test_segment_silence_defaults_in_continuous_mode() {
+		let mut config = Config::default();
+		assert_eq!(config.segment_silence_ms(), None);
+
+		config.continuous_mode = true;
+		assert_eq!(config.segment_silence_ms(), Some(DEFAULT_SEGMENT_SILENCE_MS));
+
+		config.auto_stop_silence_ms = Some(800);
+		assert_eq!(config.segment_silence_ms(), Some(800));
+	}
+
+	#[test]
+	fn test_apply_patch_changes_only_given_fields() {
+		let config = Config {
+			n_threads: Some(4),
+			..Config::default()
+		};
+		let patched = config
+			.apply_patch(&serde_json::json!({
+				"language": "it",
+				"n_threads": null,
+				"model_settings": { "ggml-tiny": { "n_threads": 2 } }
+			}))
+			.unwrap();
+
+		assert_eq!(patched.language, "it");
+		assert_eq!(patched.n_threads, None);
+		assert_eq!(patched.model_settings["ggml-tiny"].n_threads, Some(2));
+		assert_eq!(patched.hotkey, config.hotkey);
+	}
+
+	#[test]
+	fn test_apply_patch_rejects_bad_input() {
+		let config = Config::default();
+		assert!(config.apply_patch(&serde_json::json!({ "no_such_field": 1 })).is_err());
+		assert!(config.apply_patch(&serde_json::json!({ "language": 5 })).is_err());
+		assert!(config.apply_patch(&serde_json::json!(["language"])).is_err());
+	}
+
+	#[test]
+	fn test_validate_temperature_range() {
+		assert!(Config::default().validate().is_ok());
+
+		for (temperature, temperature_inc) in [(1.5, 0.2), (-0.1, 0.2), (0.0, 1.1)] {
+			let config = Config {
+				temperature,
+				temperature_inc,
+				..Config::default()
+			};
+			assert!(config.validate().is_err());
+		}
+	}
+
+	#[test]
+	fn test_transcription_timeout() {
+		let config = Config::default();
+		assert_eq!(config.transcription_timeout(0.0), Duration::from_secs(60));
+		assert_eq!(config.transcription_timeout(30.0), Duration::from_secs(360));
+
+		let config = Config {
+			transcription_timeout_secs: Some(15),
+			..Config::default()
+		};
+		assert_eq!(config.transcription_timeout(30.0), Duration::from_secs(15));
+	}
+
+	#[test]
+	fn test_validate_profile_name() {
+		assert!(validate_profile_name("work-laptop_2").is_ok());
+		assert!(validate_profile_name("Meeting notes").is_ok());
+		assert!(validate_profile_name("").is_err());
+		assert!(validate_profile_name("../config").is_err());
+		assert!(validate_profile_name("a/b").is_err());
+	}
+
+	#[test]
+	fn test_save_new_refuses_existing_file() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("work.json");
+
+		Config::default().save_new_to(&path).unwrap();
+		let err = Config::default().save_new_to(&path).unwrap_err();
+		assert!(err.to_string().contains("already exists"));
+	}
+
+	#[test]
+	fn test_detect_models_missing_dir_is_empty() {
+		let dir = tempfile::tempdir().unwrap();
+		let models = Config::detect_models_in(&dir.path().join("missing")).unwrap();
+		assert!(models.is_empty());
+	}
 }
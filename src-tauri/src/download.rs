@@ -0,0 +1,200 @@
+use crate::config::ModelMetadata;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How far back the download speed is averaged over
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Progress of a download, emitted as "download-progress"
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+	pub downloaded_bytes: u64,
+	/// Size sent by the server, if any
+	pub total_bytes: Option<u64>,
+	/// Speed over the last few seconds
+	pub bytes_per_sec: f64,
+	/// Time left at the current speed, when the size is known
+	pub eta_secs: Option<u64>,
+}
+
+/// Measures download speed over a rolling window so that it follows
+/// changes in throughput instead of averaging the whole download
+#[derive(Debug, Default)]
+struct RateTracker {
+	samples: VecDeque<(Instant, u64)>,
+}
+
+impl RateTracker {
+	fn record(&mut self, at: Instant, downloaded: u64) {
+		self.samples.push_back((at, downloaded));
+		let Some(window_start) = at.checked_sub(RATE_WINDOW) else {
+			return;
+		};
+		// Keep one sample at or before the window start to measure from
+		while self.samples.len() > 2 && self.samples[1].0 <= window_start {
+			self.samples.pop_front();
+		}
+	}
+
+	fn bytes_per_sec(&self) -> f64 {
+		let (Some(first), Some(last)) = (self.samples.front(), self.samples.back()) else {
+			return 0.0;
+		};
+		let elapsed = last.0.duration_since(first.0).as_secs_f64();
+		if elapsed == 0.0 {
+			return 0.0;
+		}
+		(last.1 - first.1) as f64 / elapsed
+	}
+
+	fn progress(&self, downloaded: u64, total: Option<u64>) -> DownloadProgress {
+		let bytes_per_sec = self.bytes_per_sec();
+		let eta_secs = total
+			.filter(|_| bytes_per_sec > 0.0)
+			.map(|total| (total.saturating_sub(downloaded) as f64 / bytes_per_sec).ceil() as u64);
+
+		DownloadProgress {
+			downloaded_bytes: downloaded,
+			total_bytes: total,
+			bytes_per_sec,
+			eta_secs,
+		}
+	}
+}
+
+/// Downloads `url` to `dest`, reporting the bytes received so far, the
+/// total size when the server sends one, speed and time left. The file is
+/// written next to `dest` first and only moved into place once complete.
+/// Setting `cancel` stops the download and removes the partial file.
+pub async fn download_file(
+	url: &str,
+	dest: &Path,
+	cancel: &AtomicBool,
+	mut on_progress: impl FnMut(&DownloadProgress),
+) -> Result<()> {
+	let mut response = reqwest::get(url)
+		.await
+		.with_context(|| format!("Failed to request {}", url))?
+		.error_for_status()
+		.with_context(|| format!("Failed to download {}", url))?;
+
+	let total = response.content_length();
+	let partial = partial_path(dest);
+	let mut file = File::create(&partial).context("Failed to create download file")?;
+	let mut downloaded = 0u64;
+	let mut rate = RateTracker::default();
+	rate.record(Instant::now(), 0);
+
+	let result: Result<()> = async {
+		while let Some(chunk) = response.chunk().await.context("Download interrupted")? {
+			if cancel.load(Ordering::SeqCst) {
+				anyhow::bail!("Download cancelled");
+			}
+			file.write_all(&chunk).context("Failed to write download file")?;
+			downloaded += chunk.len() as u64;
+			rate.record(Instant::now(), downloaded);
+			on_progress(&rate.progress(downloaded, total));
+		}
+		file.flush().context("Failed to write download file")?;
+
+		if let Some(total) = total
+			&& downloaded != total
+		{
+			anyhow::bail!("Download incomplete: got {} of {} bytes", downloaded, total);
+		}
+		Ok(())
+	}
+	.await;
+
+	drop(file);
+	if let Err(e) = result {
+		let _ = fs::remove_file(&partial);
+		return Err(e);
+	}
+
+	fs::rename(&partial, dest).context("Failed to move downloaded file into place")
+}
+
+/// Checks that `path` looks like a ggml model by reading its header
+pub fn verify_model_file(path: &Path) -> Result<()> {
+	ModelMetadata::read(path).map(|_| ())
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+	let mut partial = dest.as_os_str().to_owned();
+	partial.push(".part");
+	PathBuf::from(partial)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_verify_model_file_rejects_error_pages() {
+		let dir = tempfile::tempdir().unwrap();
+		let html = dir.path().join("error.bin");
+		fs::write(&html, "<!DOCTYPE html><html><body>Not Found</body></html>").unwrap();
+		assert!(verify_model_file(&html).is_err());
+	}
+
+	#[test]
+	fn test_cancelled_download_leaves_no_files() {
+		use std::io::Read;
+		use std::net::TcpListener;
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/ggml-base.bin", listener.local_addr().unwrap());
+		std::thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+			let _ = stream.read(&mut [0; 1024]);
+			let body = [0u8; 4096];
+			let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len());
+			let _ = stream.write_all(&body);
+		});
+
+		let dir = tempfile::tempdir().unwrap();
+		let dest = dir.path().join("ggml-base.bin");
+		let cancel = AtomicBool::new(true);
+
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		let result = runtime.block_on(download_file(&url, &dest, &cancel, |_| {}));
+
+		assert!(result.is_err());
+		assert!(!dest.exists());
+		assert!(!partial_path(&dest).exists());
+	}
+
+	#[test]
+	fn test_rate_tracker_uses_recent_window() {
+		let start = Instant::now();
+		let mut rate = RateTracker::default();
+		rate.record(start, 0);
+		assert_eq!(rate.bytes_per_sec(), 0.0);
+
+		// A slow start, then 1000 bytes per second
+		rate.record(start + Duration::from_secs(10), 1_000);
+		for secs in 11..=20 {
+			rate.record(start + Duration::from_secs(secs), 1_000 * (secs - 9));
+		}
+		assert_eq!(rate.bytes_per_sec(), 1_000.0);
+
+		let progress = rate.progress(11_000, Some(16_000));
+		assert_eq!(progress.eta_secs, Some(5));
+		assert_eq!(rate.progress(11_000, None).eta_secs, None);
+	}
+
+	#[test]
+	fn test_partial_path_appends_suffix() {
+		assert_eq!(
+			partial_path(Path::new("/models/ggml-base.bin")),
+			PathBuf::from("/models/ggml-base.bin.part")
+		);
+	}
+}
@@ -0,0 +1,240 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Brings the application or window matching `name` to the front
+#[cfg(target_os = "macos")]
+pub fn focus_app(name: &str) -> Result<()> {
+	let script = format!(
+		"tell application \"{}\" to activate",
+		name.replace('\\', "\\\\").replace('"', "\\\"")
+	);
+	let status = Command::new("osascript")
+		.args(["-e", &script])
+		.status()
+		.context("Failed to run osascript")?;
+
+	if !status.success() {
+		anyhow::bail!("Application not found: {}", name);
+	}
+	Ok(())
+}
+
+/// Brings the application or window matching `name` to the front
+#[cfg(target_os = "windows")]
+pub fn focus_app(name: &str) -> Result<()> {
+	let script = format!(
+		"(New-Object -ComObject WScript.Shell).AppActivate('{}')",
+		name.replace('\'', "''")
+	);
+	let output = Command::new("powershell")
+		.args(["-NoProfile", "-NonInteractive", "-Command", &script])
+		.output()
+		.context("Failed to run powershell")?;
+
+	// AppActivate prints whether it found a matching window
+	if String::from_utf8_lossy(&output.stdout).trim() != "True" {
+		anyhow::bail!("Window not found: {}", name);
+	}
+	Ok(())
+}
+
+/// Brings the window whose title or class matches `name` to the front.
+/// Needs `wmctrl` or `xdotool`, which only work on X11.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn focus_app(name: &str) -> Result<()> {
+	let attempts: [(&str, &[&str]); 2] = [
+		("wmctrl", &["-a", name]),
+		("xdotool", &["search", "--class", name, "windowactivate"]),
+	];
+
+	for (program, args) in attempts {
+		match Command::new(program).args(args).status() {
+			Ok(status) if status.success() => return Ok(()),
+			Ok(_) => anyhow::bail!("Window not found: {}", name),
+			// Not installed, try the next tool
+			Err(_) => continue,
+		}
+	}
+
+	anyhow::bail!("Focusing other windows needs wmctrl or xdotool to be installed")
+}
+
+/// A window of another application that can be focused again later
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowHandle {
+	/// Process owning the window
+	pub pid: u32,
+	/// X11 window id or Windows `HWND`, macOS activates by process
+	pub window_id: Option<String>,
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+	let output = Command::new(program).args(args).output().ok()?;
+	output
+		.status
+		.success()
+		.then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The frontmost window, unless it belongs to this app
+#[cfg(target_os = "macos")]
+pub fn active_window() -> Option<WindowHandle> {
+	let pid = command_output(
+		"osascript",
+		&[
+			"-e",
+			"tell application \"System Events\" to get unix id of first application process whose frontmost is true",
+		],
+	)?
+	.parse()
+	.ok()?;
+
+	(pid != std::process::id()).then_some(WindowHandle { pid, window_id: None })
+}
+
+/// Brings a window remembered by `active_window` back to the front
+#[cfg(target_os = "macos")]
+pub fn restore_focus(window: &WindowHandle) -> Result<()> {
+	let script = format!(
+		"tell application \"System Events\" to set frontmost of first application process whose unix id is {} to true",
+		window.pid
+	);
+	let status = Command::new("osascript")
+		.args(["-e", &script])
+		.status()
+		.context("Failed to run osascript")?;
+
+	if !status.success() {
+		anyhow::bail!("Application {} is no longer running", window.pid);
+	}
+	Ok(())
+}
+
+/// Classes of the taskbar windows that take the foreground while the tray
+/// icon is clicked
+#[cfg(target_os = "windows")]
+const TASKBAR_CLASSES: [&str; 3] = [
+	"Shell_TrayWnd",
+	"NotifyIconOverflowWindow",
+	"TopLevelWindowForOverflowXamlIsland",
+];
+
+/// Process and class of a top-level window
+#[cfg(target_os = "windows")]
+fn window_owner(hwnd: windows_sys::Win32::Foundation::HWND) -> (u32, String) {
+	use windows_sys::Win32::UI::WindowsAndMessaging::{GetClassNameW, GetWindowThreadProcessId};
+
+	// SAFETY: plain Win32 queries, the buffers outlive the calls
+	unsafe {
+		let mut pid = 0;
+		GetWindowThreadProcessId(hwnd, &mut pid);
+		let mut class = [0u16; 64];
+		let len = GetClassNameW(hwnd, class.as_mut_ptr(), class.len() as i32);
+		(pid, String::from_utf16_lossy(&class[..len.max(0) as usize]))
+	}
+}
+
+/// The foreground window, unless it belongs to this app. While the taskbar
+/// is in front after a tray click, the topmost app window below it is the
+/// one that had the foreground before.
+#[cfg(target_os = "windows")]
+pub fn active_window() -> Option<WindowHandle> {
+	use windows_sys::Win32::UI::WindowsAndMessaging::{
+		GW_HWNDNEXT, GWL_EXSTYLE, GetForegroundWindow, GetTopWindow, GetWindow, GetWindowLongW,
+		GetWindowTextLengthW, IsIconic, IsWindowVisible, WS_EX_TOOLWINDOW,
+	};
+
+	let own_pid = std::process::id();
+	let handle = |hwnd: windows_sys::Win32::Foundation::HWND, pid: u32| WindowHandle {
+		pid,
+		window_id: Some((hwnd as usize).to_string()),
+	};
+
+	// SAFETY: plain Win32 queries on handles the system just returned
+	unsafe {
+		let foreground = GetForegroundWindow();
+		if foreground.is_null() {
+			return None;
+		}
+		let (pid, class) = window_owner(foreground);
+		if pid == 0 || pid == own_pid {
+			return None;
+		}
+		if !TASKBAR_CLASSES.contains(&class.as_str()) {
+			return Some(handle(foreground, pid));
+		}
+
+		let mut hwnd = GetTopWindow(std::ptr::null_mut());
+		while !hwnd.is_null() {
+			let is_app_window = IsWindowVisible(hwnd) != 0
+				&& IsIconic(hwnd) == 0
+				&& GetWindowTextLengthW(hwnd) > 0
+				&& GetWindowLongW(hwnd, GWL_EXSTYLE) as u32 & WS_EX_TOOLWINDOW == 0;
+			if is_app_window {
+				let (pid, class) = window_owner(hwnd);
+				if pid == own_pid {
+					return None;
+				}
+				if pid != 0 && !TASKBAR_CLASSES.contains(&class.as_str()) {
+					return Some(handle(hwnd, pid));
+				}
+			}
+			hwnd = GetWindow(hwnd, GW_HWNDNEXT);
+		}
+		None
+	}
+}
+
+/// Brings a window remembered by `active_window` back to the front
+#[cfg(target_os = "windows")]
+pub fn restore_focus(window: &WindowHandle) -> Result<()> {
+	use windows_sys::Win32::UI::WindowsAndMessaging::{IsWindow, SetForegroundWindow};
+
+	let Some(hwnd) = window.window_id.as_deref().and_then(|id| id.parse::<usize>().ok()) else {
+		return Ok(());
+	};
+	let hwnd = hwnd as windows_sys::Win32::Foundation::HWND;
+
+	// SAFETY: a stale handle is rejected by IsWindow, not dereferenced
+	unsafe {
+		if IsWindow(hwnd) == 0 {
+			anyhow::bail!("Application {} is no longer running", window.pid);
+		}
+		if SetForegroundWindow(hwnd) == 0 {
+			anyhow::bail!("Windows refused to focus application {}", window.pid);
+		}
+	}
+	Ok(())
+}
+
+/// The active window, unless it belongs to this app. Needs `xdotool`, so
+/// it's `None` on Wayland or without it installed.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn active_window() -> Option<WindowHandle> {
+	let window_id = command_output("xdotool", &["getactivewindow"])?;
+	let pid = command_output("xdotool", &["getwindowpid", &window_id])
+		.and_then(|pid| pid.parse().ok())
+		.unwrap_or(0);
+
+	(pid != std::process::id()).then_some(WindowHandle {
+		pid,
+		window_id: Some(window_id),
+	})
+}
+
+/// Brings a window remembered by `active_window` back to the front
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn restore_focus(window: &WindowHandle) -> Result<()> {
+	let Some(ref window_id) = window.window_id else {
+		return Ok(());
+	};
+	let status = Command::new("xdotool")
+		.args(["windowactivate", window_id])
+		.status()
+		.context("Failed to run xdotool")?;
+
+	if !status.success() {
+		anyhow::bail!("Window {} no longer exists", window_id);
+	}
+	Ok(())
+}
@@ -1,32 +1,96 @@
 use serde::{Deserialize, Serialize};
 
+/// GPU backends whisper-rs was compiled with
+pub const COMPILED_BACKENDS: &[&str] = &["vulkan"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GpuDevice {
+    /// Device index passed to whisper-rs, -1 for adapters this build can't use
     pub id: i32,
     pub name: String,
     pub device_type: String,
     pub backend: String,
+    /// The whisper-rs backend that would run on this adapter, if any
+    pub whisper_backend: Option<String>,
+    /// Memory of the adapter itself, known for Vulkan adapters
+    pub memory_mb: Option<u64>,
+}
+
+/// Whether `preference` ("auto" or a backend name) can be honoured by this build
+pub fn is_backend_supported(preference: &str) -> bool {
+    preference.eq_ignore_ascii_case("auto")
+        || COMPILED_BACKENDS
+            .iter()
+            .any(|b| b.eq_ignore_ascii_case(preference))
 }
 
 pub fn get_gpu_devices() -> Vec<GpuDevice> {
     let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-        backends: wgpu::Backends::VULKAN,
+        backends: wgpu::Backends::all(),
         ..Default::default()
     });
 
-    let adapters: Vec<wgpu::Adapter> = instance.enumerate_adapters(wgpu::Backends::VULKAN);
+    let adapters: Vec<wgpu::Adapter> = instance.enumerate_adapters(wgpu::Backends::all());
+
+    // whisper-rs numbers devices in the order its backend enumerates them,
+    // which for Vulkan matches wgpu's Vulkan adapter order
+    let mut next_id = 0;
 
     adapters
         .into_iter()
-        .enumerate()
-        .map(|(index, adapter)| {
+        .map(|adapter| {
             let info = adapter.get_info();
+            let whisper_backend = match info.backend {
+                wgpu::Backend::Vulkan => Some("vulkan".to_string()),
+                _ => None,
+            };
+            let memory_mb = match info.backend {
+                wgpu::Backend::Vulkan => vulkan_memory_mb(&adapter),
+                _ => None,
+            };
+            let id = if whisper_backend.is_some() {
+                next_id += 1;
+                next_id - 1
+            } else {
+                -1
+            };
+
             GpuDevice {
-                id: index as i32,
+                id,
                 name: info.name,
                 device_type: format!("{:?}", info.device_type),
                 backend: format!("{:?}", info.backend),
+                whisper_backend,
+                memory_mb,
             }
         })
         .collect()
 }
+
+/// Size of a Vulkan adapter's device-local memory heaps
+#[cfg(not(target_vendor = "apple"))]
+fn vulkan_memory_mb(adapter: &wgpu::Adapter) -> Option<u64> {
+    // SAFETY: the raw handles are only used to read properties, not destroyed
+    unsafe {
+        adapter.as_hal::<wgpu::hal::api::Vulkan, _, _>(|adapter| {
+            let adapter = adapter?;
+            let properties = adapter
+                .shared_instance()
+                .raw_instance()
+                .get_physical_device_memory_properties(adapter.raw_physical_device());
+            let bytes: u64 = properties
+                .memory_heaps_as_slice()
+                .iter()
+                .filter(|heap| heap.flags.contains(ash::vk::MemoryHeapFlags::DEVICE_LOCAL))
+                .map(|heap| heap.size)
+                .sum();
+            Some(bytes / 1_000_000).filter(|&mb| mb > 0)
+        })
+    }
+}
+
+/// wgpu has no Vulkan backend on Apple platforms
+#[cfg(target_vendor = "apple")]
+fn vulkan_memory_mb(_adapter: &wgpu::Adapter) -> Option<u64> {
+    None
+}
@@ -0,0 +1,197 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Oldest entries are dropped past this many
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+	/// Seconds since the Unix epoch
+	pub timestamp: u64,
+	pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+	pub entries: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+	Txt,
+	Csv,
+	Json,
+}
+
+impl ExportFormat {
+	pub fn parse(format: &str) -> Result<Self> {
+		match format.to_lowercase().as_str() {
+			"txt" => Ok(ExportFormat::Txt),
+			"csv" => Ok(ExportFormat::Csv),
+			"json" => Ok(ExportFormat::Json),
+			_ => anyhow::bail!("Unsupported export format: {}", format),
+		}
+	}
+}
+
+impl History {
+	pub fn history_path() -> Result<PathBuf> {
+		Ok(Config::config_dir()?.join("history.json"))
+	}
+
+	pub fn load() -> Result<Self> {
+		let history_path = Self::history_path()?;
+
+		if history_path.exists() {
+			let content =
+				fs::read_to_string(&history_path).context("Failed to read history file")?;
+			serde_json::from_str(&content).context("Failed to parse history file")
+		} else {
+			Ok(History::default())
+		}
+	}
+
+	pub fn save(&self) -> Result<()> {
+		let content = serde_json::to_string_pretty(self).context("Failed to serialize history")?;
+		fs::write(Self::history_path()?, content).context("Failed to write history file")?;
+		Ok(())
+	}
+
+	pub fn push(&mut self, text: &str) {
+		self.entries.push(HistoryEntry {
+			timestamp: now_secs(),
+			text: text.to_string(),
+		});
+
+		if self.entries.len() > MAX_HISTORY_ENTRIES {
+			let excess = self.entries.len() - MAX_HISTORY_ENTRIES;
+			self.entries.drain(..excess);
+		}
+	}
+
+	pub fn export(&self, format: ExportFormat) -> Result<String> {
+		match format {
+			ExportFormat::Txt => Ok(self
+				.entries
+				.iter()
+				.map(|e| e.text.replace(['\r', '\n'], " ") + "\n")
+				.collect()),
+			ExportFormat::Csv => {
+				let mut out = String::from("timestamp,text\n");
+				for entry in &self.entries {
+					out.push_str(&format_timestamp(entry.timestamp));
+					out.push(',');
+					out.push_str(&escape_csv(&entry.text));
+					out.push('\n');
+				}
+				Ok(out)
+			}
+			ExportFormat::Json => {
+				serde_json::to_string_pretty(&self.entries).context("Failed to serialize history")
+			}
+		}
+	}
+}
+
+pub fn now_secs() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or(0)
+}
+
+/// Formats a Unix timestamp as an ISO 8601 UTC date-time
+pub fn format_timestamp(secs: u64) -> String {
+	let days = (secs / 86_400) as i64;
+	let rem = secs % 86_400;
+
+	// Civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html
+	let z = days + 719_468;
+	let era = z.div_euclid(146_097);
+	let doe = z.rem_euclid(146_097);
+	let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+	format!(
+		"{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+		year,
+		month,
+		day,
+		rem / 3600,
+		rem % 3600 / 60,
+		rem % 60
+	)
+}
+
+fn escape_csv(field: &str) -> String {
+	if field.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn history(entries: &[(u64, &str)]) -> History {
+		History {
+			entries: entries
+				.iter()
+				.map(|&(timestamp, text)| HistoryEntry {
+					timestamp,
+					text: text.to_string(),
+				})
+				.collect(),
+		}
+	}
+
+	#[test]
+	fn test_format_timestamp() {
+		assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+		assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+		assert_eq!(format_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+	}
+
+	#[test]
+	fn test_export_csv_escapes_fields() {
+		let history = history(&[(0, "plain"), (60, "a, \"quoted\"\nline")]);
+		let csv = history.export(ExportFormat::Csv).unwrap();
+		assert_eq!(
+			csv,
+			"timestamp,text\n1970-01-01T00:00:00Z,plain\n1970-01-01T00:01:00Z,\"a, \"\"quoted\"\"\nline\"\n"
+		);
+	}
+
+	#[test]
+	fn test_export_txt_one_entry_per_line() {
+		let history = history(&[(0, "first\nsecond"), (1, "third")]);
+		assert_eq!(history.export(ExportFormat::Txt).unwrap(), "first second\nthird\n");
+	}
+
+	#[test]
+	fn test_push_caps_entries() {
+		let mut history = History::default();
+		for i in 0..MAX_HISTORY_ENTRIES + 5 {
+			history.push(&i.to_string());
+		}
+		assert_eq!(history.entries.len(), MAX_HISTORY_ENTRIES);
+		assert_eq!(history.entries[0].text, "5");
+	}
+
+	#[test]
+	fn test_export_format_parse() {
+		assert_eq!(ExportFormat::parse("CSV").unwrap(), ExportFormat::Csv);
+		assert!(ExportFormat::parse("xml").is_err());
+	}
+}
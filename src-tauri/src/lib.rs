@@ -1,49 +1,300 @@
 mod audio;
 mod config;
+mod download;
+mod focus;
 mod gpu;
+mod history;
+mod metrics;
+mod models;
+mod permissions;
+mod power;
 mod state;
+mod stats;
+mod text_processing;
 mod transcribe;
+mod typing;
+mod window_state;
 
-use audio::RecordingSession;
-use config::{Config, ModelInfo};
+use audio::{AutoStop, CaptureSettings, PcmTap, RecordingSession};
+use config::{ClipboardFormat, Config, EmptyResultAction, HotkeyAction, ModelInfo, ModelValidation, NotificationLevel, OutputMode};
+use history::HistoryEntry;
+use models::ModelRecommendation;
+use permissions::MicPermission;
 use parking_lot::Mutex;
-use state::{AppState, AppStateManager};
-use transcribe::LanguageInfo;
+use state::{
+	AppState, AppStateManager, EffectiveConfig, GpuStatus, LoadedModel, ModelLoadOutcome,
+	RecordReadiness,
+};
+use stats::Stats;
+use transcribe::{LanguageInfo, ThreadBench, TranscribeOptions, Transcriber};
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 use tauri::{
 	image::Image,
-	menu::{Menu, MenuItem},
+	menu::{CheckMenuItem, Menu, MenuItem},
 	tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-	AppHandle, Emitter, Manager,
+	AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, WebviewUrl, WebviewWindow,
+	WebviewWindowBuilder, WindowEvent, Wry,
 };
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+#[cfg(not(target_os = "linux"))]
 use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_opener::OpenerExt;
 
 static RECORDING_SESSION: Mutex<Option<RecordingSession>> = Mutex::new(None);
 
+/// Buttons that can be attached to a notification where the platform supports them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NotificationAction {
+	CopyTranscription,
+}
+
+impl NotificationAction {
+	#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+	fn id(self) -> &'static str {
+		match self {
+			NotificationAction::CopyTranscription => "copy",
+		}
+	}
+
+	#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+	fn label(self) -> &'static str {
+		match self {
+			NotificationAction::CopyTranscription => "Copy",
+		}
+	}
+}
+
+/// How important a notification is, checked against the configured level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NotificationKind {
+	Info,
+	Error,
+}
+
+impl NotificationKind {
+	fn allowed_by(self, level: NotificationLevel) -> bool {
+		match level {
+			NotificationLevel::All => true,
+			NotificationLevel::Errors => self == NotificationKind::Error,
+			NotificationLevel::None => false,
+		}
+	}
+}
+
+/// Tray menu entries whose state has to be kept in sync with the config
+struct TrayMenuItems {
+	autostart: CheckMenuItem<Wry>,
+	hotkey_active: CheckMenuItem<Wry>,
+}
+
 #[tauri::command]
 fn get_app_state(state: tauri::State<Arc<AppStateManager>>) -> AppState {
 	state.get_state()
 }
 
+/// Display label for a hotkey string, e.g. "super+r" becomes "⌘R" on macOS
+#[tauri::command]
+fn format_hotkey(hotkey: String) -> Result<String, String> {
+	parse_hotkey(&hotkey)
+		.map(|shortcut| format_shortcut(&shortcut))
+		.ok_or_else(|| format!("Invalid hotkey: {}", hotkey))
+}
+
 #[tauri::command]
 fn get_config(state: tauri::State<Arc<AppStateManager>>) -> Config {
 	state.get_config()
 }
 
+#[tauri::command]
+fn get_effective_config(state: tauri::State<Arc<AppStateManager>>) -> EffectiveConfig {
+	state.effective_config()
+}
+
+/// Everything useful for a bug report, in one serializable bundle
+#[derive(Debug, Clone, serde::Serialize)]
+struct Diagnostics {
+	app_version: String,
+	os: &'static str,
+	arch: &'static str,
+	state: AppState,
+	effective_config: EffectiveConfig,
+	loaded_model: Option<LoadedModel>,
+	gpu_devices: Vec<gpu::GpuDevice>,
+	audio: audio::AudioHostInfo,
+	last_error: Option<String>,
+}
+
+/// Reduces a path to its file name so reports don't reveal directory layouts
+fn redact_path(path: &str) -> String {
+	Path::new(path)
+		.file_name()
+		.map(|name| name.to_string_lossy().to_string())
+		.unwrap_or_default()
+}
+
+/// Collects diagnostics to paste into an issue. With `redact_paths` only
+/// the file names of models and other configured files are included.
+#[tauri::command]
+fn get_diagnostics(
+	app: AppHandle,
+	state: tauri::State<Arc<AppStateManager>>,
+	redact_paths: bool,
+) -> Diagnostics {
+	let mut effective_config = state.effective_config();
+	let mut loaded_model = state.loaded_model();
+
+	if redact_paths {
+		let config = &mut effective_config.config;
+		config.map_paths(redact_path);
+		// May embed paths or tokens in its arguments
+		config.post_command = config.post_command.as_ref().map(|_| "<redacted>".to_string());
+		effective_config.loaded_model_path =
+			effective_config.loaded_model_path.as_deref().map(redact_path);
+		if let Some(ref mut model) = loaded_model {
+			model.path = redact_path(&model.path);
+		}
+	}
+
+	let audio = audio::get_audio_host_info(effective_config.config.audio_host.as_deref());
+
+	Diagnostics {
+		app_version: app.package_info().version.to_string(),
+		os: std::env::consts::OS,
+		arch: std::env::consts::ARCH,
+		state: state.get_state(),
+		effective_config,
+		loaded_model,
+		gpu_devices: gpu::get_gpu_devices(),
+		audio,
+		last_error: state.get_error(),
+	}
+}
+
+/// Word error rate of a transcription against a known-correct reference
+#[tauri::command]
+fn compute_wer(hypothesis: String, reference: String) -> metrics::WerResult {
+	metrics::word_error_rate(&hypothesis, &reference)
+}
+
+#[tauri::command]
+fn gpu_status(state: tauri::State<Arc<AppStateManager>>) -> GpuStatus {
+	state.gpu_status()
+}
+
+/// Predicted seconds needed to transcribe `audio_secs` of audio
+#[tauri::command]
+fn estimate_transcription_time(state: tauri::State<Arc<AppStateManager>>, audio_secs: f32) -> f32 {
+	state.estimate_transcription_time(audio_secs)
+}
+
+/// The model actually loaded, after any fallback
+#[tauri::command]
+fn get_loaded_model(state: tauri::State<Arc<AppStateManager>>) -> Option<LoadedModel> {
+	state.loaded_model()
+}
+
+/// Applies a partial config, leaving fields the patch doesn't mention as
+/// they are, and returns the resulting config. Changed model fields load
+/// the model they now point to.
+#[tauri::command]
+async fn patch_config(
+	app: AppHandle,
+	state: tauri::State<'_, Arc<AppStateManager>>,
+	patch: serde_json::Value,
+) -> Result<Config, String> {
+	let old_config = state.get_config();
+	let had_model = state.has_model();
+	let config = old_config.apply_patch(&patch).map_err(|e| format!("{:#}", e))?;
+	save_config(app.clone(), state.clone(), config)?;
+
+	let config = state.get_config();
+	let model_changed = old_config.model_path != config.model_path
+		|| old_config.low_power_model != config.low_power_model
+		|| old_config.fallback_models != config.fallback_models;
+	// save_config has already reloaded it for a GPU change
+	let gpu_reloaded = had_model
+		&& (old_config.use_gpu != config.use_gpu || old_config.gpu_device != config.gpu_device);
+	if model_changed && !gpu_reloaded && let Some(model_path) = config.model_path {
+		let _ = app.emit("model-loading", &model_path);
+		let manager = state.inner().clone();
+		let result = tauri::async_runtime::spawn_blocking(move || manager.reload_model())
+			.await
+			.map_err(|e| e.to_string())?;
+		let outcome = match result {
+			Ok(outcome) => outcome,
+			Err(e) => {
+				let _ = app.emit("model-load-failed", e.to_string());
+				return Err(format!("Failed to reload model: {}", e));
+			}
+		};
+		// The low-power model or a fallback may have been loaded instead
+		if let Some(loaded) = state.loaded_model() {
+			let _ = app.emit("model-loaded", loaded.path);
+		}
+		report_load_outcome(&app, &outcome);
+	}
+
+	Ok(state.get_config())
+}
+
 #[tauri::command]
 fn save_config(
 	app: AppHandle,
 	state: tauri::State<Arc<AppStateManager>>,
-	config: Config,
+	mut config: Config,
 ) -> Result<(), String> {
 	let old_config = state.get_config();
-	state.update_config(config.clone()).map_err(|e| e.to_string())?;
+	// Tracked here rather than by the frontend, whose copy may be stale
+	config.previous_language = if old_config.language != config.language {
+		Some(old_config.language.clone())
+	} else {
+		old_config.previous_language.clone()
+	};
 
-	if old_config.hotkey != config.hotkey {
+	// Registered before saving, and rolled back if either step fails, so the
+	// saved hotkeys are always the ones that work
+	let hotkeys_changed =
+		old_config.hotkey != config.hotkey || old_config.action_hotkeys != config.action_hotkeys;
+	if hotkeys_changed {
 		let _ = app.global_shortcut().unregister_all();
-		setup_global_shortcut(&app, &config.hotkey)?;
+		if let Err(e) = setup_global_shortcuts(&app, &config) {
+			restore_global_shortcuts(&app, &old_config);
+			return Err(e);
+		}
+	}
+	if let Err(e) = state.update_config(config.clone()) {
+		if hotkeys_changed {
+			restore_global_shortcuts(&app, &old_config);
+		}
+		return Err(e.to_string());
+	}
+
+	// Picking a language by hand replaces whatever was locked in
+	if old_config.language != config.language || !config.lock_detected_language {
+		state.set_locked_language(None);
+	}
+
+	let stream_changed = old_config.keep_stream_open != config.keep_stream_open
+		|| old_config.audio_host != config.audio_host
+		|| old_config.capture_sample_rate != config.capture_sample_rate
+		|| old_config.audio_buffer_frames != config.audio_buffer_frames
+		|| old_config.preferred_devices != config.preferred_devices
+		|| old_config.loopback_devices != config.loopback_devices;
+
+	// A running recording keeps its stream, the next one picks up the change
+	if stream_changed
+		&& RECORDING_SESSION.lock().is_none()
+		&& state.get_state() != AppState::Calibrating
+	{
+		sync_persistent_stream(&config);
 	}
 
 	// Handle GPU config change - reload model if needed
@@ -52,286 +303,1850 @@ fn save_config(
 
 	if gpu_changed && state.has_model() {
 		match state.reload_model() {
-			Ok(fell_back) => {
-				if fell_back {
-					show_notification(
-						&app,
-						"GPU Unavailable",
-						"Failed to use GPU acceleration, using CPU instead",
-					);
-					let _ = app.emit("gpu-fallback", ());
-				}
-			}
+			Ok(outcome) => report_load_outcome(&app, &outcome),
 			Err(e) => {
 				return Err(format!("Failed to reload model: {}", e));
 			}
 		}
 	}
 
-	Ok(())
+	Ok(())
+}
+
+/// Opens or closes the always-on input stream to match `keep_stream_open`
+fn sync_persistent_stream(config: &Config) {
+	if config.keep_stream_open {
+		if let Err(e) = audio::open_persistent_stream(&CaptureSettings::from_config(config)) {
+			eprintln!("Failed to open input stream: {}", e);
+		}
+	} else {
+		audio::close_persistent_stream();
+	}
+}
+
+/// Saves the active config under a new profile name without switching to it
+#[tauri::command]
+fn clone_profile(state: tauri::State<Arc<AppStateManager>>, new_name: String) -> Result<(), String> {
+	state
+		.get_config()
+		.save_as_profile(&new_name)
+		.map_err(|e| format!("{:#}", e))
+}
+
+#[tauri::command]
+fn validate_hotkey(app: AppHandle, hotkey: String) -> Result<(), String> {
+	let shortcut = parse_hotkey(&hotkey).ok_or_else(|| format!("Invalid hotkey: {}", hotkey))?;
+	let global_shortcut = app.global_shortcut();
+
+	// Already registered by us, e.g. the currently active hotkey
+	if global_shortcut.is_registered(shortcut) {
+		return Ok(());
+	}
+
+	global_shortcut
+		.register(shortcut)
+		.map_err(|e| format!("Hotkey {} is already in use: {}", hotkey, e))?;
+	let _ = global_shortcut.unregister(shortcut);
+
+	Ok(())
+}
+
+#[tauri::command]
+fn get_available_models(
+	state: tauri::State<Arc<AppStateManager>>,
+) -> Result<Vec<ModelInfo>, String> {
+	state.get_config().detect_models().map_err(|e| e.to_string())
+}
+
+/// Times a built-in clip with each thread count, to help pick `n_threads`
+#[tauri::command]
+async fn benchmark_threads(
+	state: tauri::State<'_, Arc<AppStateManager>>,
+	model_path: String,
+	counts: Vec<i32>,
+) -> Result<Vec<ThreadBench>, String> {
+	if counts.is_empty() || counts.iter().any(|&count| count < 1) {
+		return Err("Thread counts must be at least 1".to_string());
+	}
+
+	let config = state.get_config();
+	tauri::async_runtime::spawn_blocking(move || {
+		// A separate instance so the benchmark can't disturb dictation
+		let transcriber = Transcriber::new(&model_path, config.use_gpu, config.gpu_device)
+			.map_err(|e| e.to_string())?
+			.transcriber;
+		transcriber
+			.benchmark_threads(&audio::benchmark_clip(), &counts, &AtomicBool::new(false))
+			.map_err(|e| e.to_string())
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+/// Checks every detected model's header without loading it
+#[tauri::command]
+async fn validate_all_models(
+	state: tauri::State<'_, Arc<AppStateManager>>,
+) -> Result<Vec<ModelValidation>, String> {
+	let config = state.get_config();
+	tauri::async_runtime::spawn_blocking(move || {
+		let models = config.detect_models().map_err(|e| e.to_string())?;
+		Ok(models.iter().map(|model| ModelValidation::check(&model.path)).collect())
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn load_model(
+	app: AppHandle,
+	state: tauri::State<'_, Arc<AppStateManager>>,
+	model_path: String,
+) -> Result<(), String> {
+	let _ = app.emit("model-loading", &model_path);
+
+	// Loading is synchronous and can take a while for large models
+	let state = state.inner().clone();
+	let path = model_path.clone();
+	let result = tauri::async_runtime::spawn_blocking(move || state.load_model(&path))
+		.await
+		.map_err(|e| e.to_string())?;
+
+	let outcome = match result {
+		Ok(outcome) => outcome,
+		Err(e) => {
+			let _ = app.emit("model-load-failed", e.to_string());
+			return Err(e.to_string());
+		}
+	};
+
+	let loaded = outcome.fallback_model.as_deref().unwrap_or(&model_path);
+	let _ = app.emit("model-loaded", loaded);
+	report_load_outcome(&app, &outcome);
+
+	Ok(())
+}
+
+/// Progress of `quick_setup`, emitted as "setup-progress"
+#[derive(Debug, Clone, serde::Serialize)]
+struct SetupProgress {
+	/// "downloading", "verifying", "loading" or "done"
+	stage: &'static str,
+	downloaded: u64,
+	total: Option<u64>,
+}
+
+/// Opens the last saved recording in the system's default audio player
+#[tauri::command]
+fn open_last_recording(
+	app: AppHandle,
+	state: tauri::State<Arc<AppStateManager>>,
+) -> Result<(), String> {
+	if !state.get_config().save_recordings {
+		return Err("Saving recordings is disabled".to_string());
+	}
+
+	let path = state
+		.last_recording_path
+		.lock()
+		.clone()
+		.filter(|path| path.exists())
+		.ok_or_else(|| "No saved recording".to_string())?;
+
+	app.opener()
+		.open_path(path.to_string_lossy(), None::<&str>)
+		.map_err(|e| e.to_string())
+}
+
+/// Accepts or discards the transcription waiting for review
+#[tauri::command]
+fn resolve_review(state: tauri::State<Arc<AppStateManager>>, accept: bool) -> Result<(), String> {
+	let sender = state
+		.pending_review
+		.lock()
+		.take()
+		.ok_or_else(|| "No transcription is awaiting review".to_string())?;
+	sender.send(accept).map_err(|e| e.to_string())
+}
+
+/// Stops the model download in progress, if any
+#[tauri::command]
+fn cancel_download(state: tauri::State<Arc<AppStateManager>>) {
+	state.cancel_download.store(true, Ordering::SeqCst);
+}
+
+/// Downloads the base model matching the system locale, unless it is
+/// already present, and loads it.
+#[tauri::command]
+async fn quick_setup(
+	app: AppHandle,
+	state: tauri::State<'_, Arc<AppStateManager>>,
+) -> Result<(), String> {
+	let locale = sys_locale::get_locale();
+	let name = models::quick_setup_model_name(locale.as_deref());
+	let dest = Config::models_dir().map_err(|e| e.to_string())?.join(name);
+
+	let emit_progress = |stage, downloaded, total| {
+		let _ = app.emit("setup-progress", SetupProgress { stage, downloaded, total });
+	};
+
+	if download::verify_model_file(&dest).is_err() {
+		// Report roughly every percent, or every MiB when the size is unknown
+		let step = |total: Option<u64>| total.map_or(1 << 20, |t| (t / 100).max(1));
+		let mut next_report = 0;
+		state.cancel_download.store(false, Ordering::SeqCst);
+		let result = download::download_file(
+			&models::model_url(name),
+			&dest,
+			&state.cancel_download,
+			|progress| {
+				if progress.downloaded_bytes >= next_report {
+					next_report = progress.downloaded_bytes + step(progress.total_bytes);
+					emit_progress("downloading", progress.downloaded_bytes, progress.total_bytes);
+					let _ = app.emit("download-progress", progress);
+				}
+			},
+		)
+		.await;
+
+		if let Err(e) = result {
+			if state.cancel_download.load(Ordering::SeqCst) {
+				let _ = app.emit("download-cancelled", name);
+			}
+			return Err(e.to_string());
+		}
+
+		emit_progress("verifying", 0, None);
+		if let Err(e) = download::verify_model_file(&dest) {
+			let _ = std::fs::remove_file(&dest);
+			return Err(e.to_string());
+		}
+	}
+
+	emit_progress("loading", 0, None);
+	load_model(app.clone(), state, dest.to_string_lossy().to_string()).await?;
+	emit_progress("done", 0, None);
+
+	Ok(())
+}
+
+#[tauri::command]
+async fn transcribe_file_range(
+	state: tauri::State<'_, Arc<AppStateManager>>,
+	path: String,
+	start_ms: u32,
+	duration_ms: u32,
+	language: Option<String>,
+) -> Result<String, String> {
+	let state = state.inner().clone();
+	tauri::async_runtime::spawn_blocking(move || {
+		let config = state.get_config();
+		let mut options = TranscribeOptions::from_config(&config);
+		if let Some(ref language) = language {
+			options.language = Some(language.as_str());
+		}
+		// Re-running a segment shouldn't feed earlier recordings into it
+		options.carry_context = false;
+
+		let text = {
+			let transcriber = state.transcriber.lock();
+			let transcriber = transcriber.as_ref().ok_or_else(|| "No model loaded".to_string())?;
+			transcriber
+				.transcribe_file_range(
+					Path::new(&path),
+					start_ms,
+					duration_ms,
+					&options,
+					&state.cancel_transcription,
+				)
+				.map_err(|e| e.to_string())?
+		};
+
+		Ok(apply_corrections(text, &config))
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+/// Transcribes the WAV file whose path is on the clipboard
+#[tauri::command]
+async fn transcribe_clipboard_path(
+	app: AppHandle,
+	state: tauri::State<'_, Arc<AppStateManager>>,
+) -> Result<String, String> {
+	let text = app.clipboard().read_text().map_err(|e| e.to_string())?;
+	let path = audio::audio_path_from_text(&text).map_err(|e| e.to_string())?;
+
+	let state = state.inner().clone();
+	tauri::async_runtime::spawn_blocking(move || {
+		let config = state.get_config();
+		let options = TranscribeOptions::from_config(&config);
+
+		let text = {
+			let transcriber = state.transcriber.lock();
+			let transcriber = transcriber.as_ref().ok_or_else(|| "No model loaded".to_string())?;
+			transcriber
+				.transcribe_file(&path, &options, &state.cancel_transcription)
+				.map_err(|e| e.to_string())?
+		};
+
+		Ok(apply_corrections(text, &config))
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+/// Records `duration_ms` of ambient audio while the user stays silent and
+/// returns its noise level, a baseline for silence and noise thresholds
+#[tauri::command]
+async fn measure_noise_floor(
+	app: AppHandle,
+	state: tauri::State<'_, Arc<AppStateManager>>,
+	duration_ms: u64,
+) -> Result<f32, String> {
+	if !(500..=10_000).contains(&duration_ms) {
+		return Err("Duration must be between 500 and 10000 ms".to_string());
+	}
+
+	let state = state.inner().clone();
+	tauri::async_runtime::spawn_blocking(move || {
+		// Holds off recordings for the duration, the hotkey is ignored meanwhile
+		if !state.try_set_state(AppState::Idle, AppState::Calibrating) {
+			return Err("Can't measure while recording or transcribing".to_string());
+		}
+		let _ = app.emit("state-changed", AppState::Calibrating);
+
+		let config = state.get_config();
+		let recording = RecordingSession::start(
+			None,
+			&CaptureSettings::from_config(&config),
+			config.keep_stream_open,
+		)
+		.and_then(|session| {
+			std::thread::sleep(Duration::from_millis(duration_ms));
+			session.stop(config.input_gain_db)
+		});
+		state.set_state(AppState::Idle);
+		let _ = app.emit("state-changed", AppState::Idle);
+
+		let level = audio::noise_floor(&recording.map_err(|e| e.to_string())?.samples);
+		if level > audio::MAX_PLAUSIBLE_NOISE_FLOOR {
+			return Err(format!(
+				"Too loud to measure the noise floor (level {:.3}), stay silent and try again",
+				level
+			));
+		}
+		Ok(level)
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+/// Transcribes mono audio handed in directly, skipping the microphone
+#[tauri::command]
+async fn transcribe_samples(
+	state: tauri::State<'_, Arc<AppStateManager>>,
+	samples: Vec<f32>,
+	sample_rate: u32,
+	language: Option<String>,
+) -> Result<String, String> {
+	audio::validate_external_samples(&samples, sample_rate).map_err(|e| e.to_string())?;
+
+	let state = state.inner().clone();
+	tauri::async_runtime::spawn_blocking(move || {
+		let samples = audio::resample_to(&samples, sample_rate, audio::TRANSCRIPTION_SAMPLE_RATE)
+			.map_err(|e| e.to_string())?;
+
+		let config = state.get_config();
+		let mut options = TranscribeOptions::from_config(&config);
+		if let Some(ref language) = language {
+			options.language = Some(language);
+		}
+
+		let text = {
+			let transcriber = state.transcriber.lock();
+			let transcriber = transcriber.as_ref().ok_or_else(|| "No model loaded".to_string())?;
+			transcriber
+				.transcribe(&samples, &options, &state.cancel_transcription)
+				.map_err(|e| e.to_string())?
+		};
+
+		Ok(apply_corrections(text, &config))
+	})
+	.await
+	.map_err(|e| e.to_string())?
+}
+
+/// Builds the initial prompt from a file of terms, one per line, and
+/// stores it along with the file path. Returns the prompt.
+#[tauri::command]
+fn set_vocabulary_file(
+	state: tauri::State<Arc<AppStateManager>>,
+	path: String,
+) -> Result<String, String> {
+	let terms = text_processing::load_wordlist(Path::new(&path)).map_err(|e| e.to_string())?;
+	if terms.is_empty() {
+		return Err("The vocabulary file has no terms".to_string());
+	}
+	// Without a model the byte count stands in, no token is shorter than a byte
+	let prompt = {
+		let transcriber = state.transcriber.lock();
+		text_processing::vocabulary_prompt(&terms, text_processing::MAX_VOCABULARY_PROMPT_TOKENS, |text| {
+			transcriber
+				.as_ref()
+				.and_then(|t| t.count_tokens(text).ok())
+				.unwrap_or(text.len())
+		})
+	};
+
+	let mut config = state.get_config();
+	config.initial_prompt = Some(prompt.clone());
+	config.vocabulary_file = Some(path);
+	state.update_config(config).map_err(|e| e.to_string())?;
+
+	Ok(prompt)
+}
+
+#[tauri::command]
+async fn restart_engine(
+	app: AppHandle,
+	state: tauri::State<'_, Arc<AppStateManager>>,
+) -> Result<(), String> {
+	// Waits for a running transcription to abort, then loads the model again
+	let state = state.inner().clone();
+	let outcome = tauri::async_runtime::spawn_blocking(move || state.restart_engine())
+		.await
+		.map_err(|e| e.to_string())?
+		.map_err(|e| e.to_string())?;
+	report_load_outcome(&app, &outcome);
+
+	Ok(())
+}
+
+#[tauri::command]
+fn is_model_loading(state: tauri::State<Arc<AppStateManager>>) -> bool {
+	state.is_model_loading()
+}
+
+#[tauri::command]
+fn has_model_loaded(state: tauri::State<Arc<AppStateManager>>) -> bool {
+	state.has_model()
+}
+
+#[tauri::command]
+fn is_model_multilingual(state: tauri::State<Arc<AppStateManager>>) -> bool {
+	state.is_multilingual()
+}
+
+#[tauri::command]
+fn get_last_transcription(state: tauri::State<Arc<AppStateManager>>) -> String {
+	state.get_last_transcription()
+}
+
+#[tauri::command]
+fn get_last_waveform(state: tauri::State<Arc<AppStateManager>>, buckets: usize) -> Vec<f32> {
+	state.get_last_waveform(buckets)
+}
+
+#[tauri::command]
+fn get_last_error(state: tauri::State<Arc<AppStateManager>>) -> Option<String> {
+	state.get_error()
+}
+
+#[tauri::command]
+fn can_record(state: tauri::State<Arc<AppStateManager>>) -> RecordReadiness {
+	state.record_readiness()
+}
+
+#[tauri::command]
+fn get_locked_language(state: tauri::State<Arc<AppStateManager>>) -> Option<String> {
+	state.get_locked_language()
+}
+
+#[tauri::command]
+fn clear_language_lock(state: tauri::State<Arc<AppStateManager>>) {
+	state.set_locked_language(None);
+}
+
+#[tauri::command]
+fn clear_error(state: tauri::State<Arc<AppStateManager>>) {
+	state.set_error(None);
+}
+
+#[tauri::command]
+fn get_models_directory() -> Result<String, String> {
+	Config::models_dir()
+		.map(|p| p.to_string_lossy().to_string())
+		.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_input_devices(state: tauri::State<Arc<AppStateManager>>) -> Result<Vec<String>, String> {
+	audio::list_input_devices(state.get_config().audio_host.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_audio_host_info(state: tauri::State<Arc<AppStateManager>>) -> audio::AudioHostInfo {
+	audio::get_audio_host_info(state.get_config().audio_host.as_deref())
+}
+
+#[tauri::command]
+fn get_input_device_details(
+	state: tauri::State<Arc<AppStateManager>>,
+) -> Result<Vec<audio::DeviceDetails>, String> {
+	let config = state.get_config();
+	audio::get_input_device_details(config.audio_host.as_deref(), &config.loopback_devices)
+		.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn recommend_model(language: String) -> ModelRecommendation {
+	models::recommend_model(&language)
+}
+
+#[tauri::command]
+fn get_supported_languages() -> Vec<LanguageInfo> {
+	transcribe::get_supported_languages()
+}
+
+#[tauri::command]
+fn set_autostart(
+	app: AppHandle,
+	state: tauri::State<Arc<AppStateManager>>,
+	enabled: bool,
+) -> Result<(), String> {
+	apply_autostart(&app, enabled)?;
+
+	let mut config = state.get_config();
+	config.autostart = enabled;
+	state.update_config(config).map_err(|e| e.to_string())?;
+
+	if let Some(items) = app.try_state::<TrayMenuItems>() {
+		let _ = items.autostart.set_checked(enabled);
+	}
+
+	Ok(())
+}
+
+#[tauri::command]
+fn get_autostart(app: AppHandle) -> bool {
+	app.autolaunch().is_enabled().unwrap_or(false)
+}
+
+#[tauri::command]
+fn get_history(state: tauri::State<Arc<AppStateManager>>) -> Vec<HistoryEntry> {
+	state.get_history()
+}
+
+#[tauri::command]
+fn export_history(
+	state: tauri::State<Arc<AppStateManager>>,
+	path: String,
+	format: String,
+) -> Result<(), String> {
+	state.export_history(&path, &format).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_stats(state: tauri::State<Arc<AppStateManager>>) -> Stats {
+	state.get_stats()
+}
+
+/// Past transcriptions in the history bucketed by word count
+#[tauri::command]
+fn get_length_histogram(state: tauri::State<Arc<AppStateManager>>) -> Vec<stats::LengthBucket> {
+	let history = state.history.lock();
+	// Tags from `tag_language` aren't part of what was said
+	stats::length_histogram(
+		history
+			.entries
+			.iter()
+			.map(|entry| text_processing::strip_language_tag(&entry.text)),
+	)
+}
+
+#[tauri::command]
+fn reset_stats(state: tauri::State<Arc<AppStateManager>>) -> Result<(), String> {
+	state.reset_stats().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_gpu_devices() -> Vec<gpu::GpuDevice> {
+	gpu::get_gpu_devices()
+}
+
+/// The best model to download for this machine's disk space, memory and GPU
+#[tauri::command]
+fn suggest_download(
+	state: tauri::State<Arc<AppStateManager>>,
+) -> Result<models::DownloadSuggestion, String> {
+	let models_dir = Config::models_dir().map_err(|e| e.to_string())?;
+	let mut resources = models::MachineResources::detect(&models_dir);
+	// Integrated GPUs share system memory and are rarely faster for whisper
+	if let Some(device) = gpu::get_gpu_devices()
+		.into_iter()
+		.find(|device| device.whisper_backend.is_some() && device.device_type == "DiscreteGpu")
+	{
+		resources.gpu_name = Some(device.name);
+		resources.gpu_memory_mb = device.memory_mb;
+	}
+
+	Ok(models::suggest_download(resources, &state.get_config().language))
+}
+
+/// How often live audio is sent to the frontend while recording
+const PCM_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Chunk of live audio from an ongoing recording
+#[derive(Clone, serde::Serialize)]
+struct PcmFrame {
+	sample_rate: u32,
+	samples: Vec<f32>,
+}
+
+/// Emits the recording's audio as `pcm-frame` events until it stops
+fn stream_pcm_frames(app: AppHandle, mut tap: PcmTap) {
+	std::thread::spawn(move || {
+		let sample_rate = tap.sample_rate();
+		loop {
+			std::thread::sleep(PCM_FRAME_INTERVAL);
+			let Some(samples) = tap.read() else {
+				break;
+			};
+			if !samples.is_empty() {
+				let _ = app.emit("pcm-frame", PcmFrame { sample_rate, samples });
+			}
+		}
+	});
+}
+
+fn toggle_recording(app: &AppHandle) {
+	let state = app.state::<Arc<AppStateManager>>();
+	let current_state = state.get_state();
+
+	match current_state {
+		AppState::Idle => {
+			if state.get_config().continuous_mode
+				&& !state.dictation_session.swap(true, Ordering::SeqCst)
+			{
+				let _ = app.emit("dictation-session", true);
+			}
+			start_recording(app);
+		}
+		AppState::Recording => {
+			// Pressing the hotkey ends the session with this last segment
+			end_dictation_session(app);
+			finish_recording(app);
+		}
+		AppState::Transcribing => end_dictation_session(app),
+		// The measurement ends by itself after a few seconds
+		AppState::Calibrating => {}
+	}
+}
+
+/// Stops continuous mode from recording again after the current segment
+fn end_dictation_session(app: &AppHandle) {
+	let state = app.state::<Arc<AppStateManager>>();
+	if state.dictation_session.swap(false, Ordering::SeqCst) {
+		let _ = app.emit("dictation-session", false);
+	}
+}
+
+fn start_recording(app: &AppHandle) {
+	let state = app.state::<Arc<AppStateManager>>();
+	if !state.has_model() {
+		end_dictation_session(app);
+		state.set_error(Some("No model loaded".to_string()));
+		let _ = app.emit("error", "No model loaded. Please load a Whisper model first.");
+		show_notification(app, NotificationKind::Error, "Error", "No model loaded");
+		return;
+	}
+
+	let config = state.get_config();
+	let auto_stop = config.segment_silence_ms().map(|silence_ms| {
+		let app = app.clone();
+		AutoStop {
+			silence_ms,
+			on_silence: Box::new(move || {
+				let state = app.state::<Arc<AppStateManager>>();
+				if state.get_state() == AppState::Recording {
+					let _ = app.emit("auto-stopped", ());
+					// Ends the segment but not a dictation session
+					finish_recording(&app);
+				}
+			}),
+		}
+	});
+
+	let started = state.begin_session(&RECORDING_SESSION, || {
+		let session = RecordingSession::start(
+			auto_stop,
+			&CaptureSettings::from_config(&config),
+			config.keep_stream_open,
+		)?;
+		let _ = app.emit("input-device-selected", session.device_name());
+		if config.stream_pcm {
+			stream_pcm_frames(app.clone(), session.pcm_tap());
+		}
+		Ok::<_, anyhow::Error>(session)
+	});
+
+	match started {
+		// Something else got there first, which continuous mode mustn't outlive
+		None => end_dictation_session(app),
+		Some(Ok(())) => {
+			state.set_error(None);
+			let _ = app.emit("state-changed", AppState::Recording);
+			update_tray_tooltip(app, "Recording...");
+		}
+		Some(Err(e)) => {
+			end_dictation_session(app);
+			state.set_error(Some(e.to_string()));
+			let _ = app.emit("error", e.to_string());
+			show_notification(
+				app,
+				NotificationKind::Error,
+				"Error",
+				&format!("Failed to start recording: {}", e),
+			);
+		}
+	}
+}
+
+/// Stops the recording and hands it to transcription in the background
+fn finish_recording(app: &AppHandle) {
+	let state = app.state::<Arc<AppStateManager>>();
+	let Some(session) = state.end_session(&RECORDING_SESSION) else {
+		return;
+	};
+	let _ = app.emit("state-changed", AppState::Transcribing);
+	update_tray_tooltip(app, "Transcribing...");
+
+	if let Some(session) = session {
+		match session.stop(state.get_config().input_gain_db) {
+			Ok(recording) => {
+				if recording.clipped_fraction > audio::CLIPPING_WARNING_FRACTION {
+					let _ = app.emit("clipping-warning", recording.clipped_fraction);
+				}
+				let samples = recording.samples;
+				state.set_last_recording(samples.clone());
+				let app_clone = app.clone();
+				std::thread::spawn(move || {
+					let state = app_clone.state::<Arc<AppStateManager>>();
+					if state.get_config().save_recordings {
+						save_recording(&state, &samples);
+					}
+					run_transcription_guarded(&app_clone, samples);
+				});
+			}
+			Err(e) => {
+				end_dictation_session(app);
+				state.set_state(AppState::Idle);
+				state.set_error(Some(e.to_string()));
+				let _ = app.emit("state-changed", AppState::Idle);
+				let _ = app.emit("error", e.to_string());
+				show_notification(
+					app,
+					NotificationKind::Error,
+					"Error",
+					&format!("Recording failed: {}", e),
+				);
+				update_tray_tooltip(app, "Idle - Press F9 to record");
+			}
+		}
+	} else {
+		state.set_state(AppState::Idle);
+		let _ = app.emit("state-changed", AppState::Idle);
+		update_tray_tooltip(app, "Idle - Press F9 to record");
+	}
+}
+
+/// Runs `process_transcription`, returning the app to idle if it panics so
+/// it never stays stuck in the transcribing state.
+fn run_transcription_guarded(app: &AppHandle, samples: Vec<f32>) {
+	let result = panic::catch_unwind(AssertUnwindSafe(|| process_transcription(app, samples)));
+
+	if let Err(payload) = result {
+		let reason = payload
+			.downcast_ref::<&str>()
+			.map(|s| s.to_string())
+			.or_else(|| payload.downcast_ref::<String>().cloned())
+			.unwrap_or_else(|| "unknown error".to_string());
+		let message = format!("Transcription crashed: {}", reason);
+		eprintln!("{}", message);
+
+		end_dictation_session(app);
+		let state = app.state::<Arc<AppStateManager>>();
+		state.set_error(Some(message.clone()));
+		let _ = app.emit("error", &message);
+		show_notification(app, NotificationKind::Error, "Transcription failed", &message);
+
+		state.set_state(AppState::Idle);
+		let _ = app.emit("state-changed", AppState::Idle);
+		update_tray_tooltip(app, "Idle - Press F9 to record");
+	}
+}
+
+fn process_transcription(app: &AppHandle, samples: Vec<f32>) {
+	let state = app.state::<Arc<AppStateManager>>();
+	let config = state.get_config();
+
+	let lock_language = config.lock_detected_language && config.language == "auto";
+	let locked_language = if lock_language { state.get_locked_language() } else { None };
+
+	let mut options = TranscribeOptions::from_config(&config);
+	if let Some(ref language) = locked_language {
+		options.language = Some(language);
+	}
+
+	if let Some(min_interval) = config.min_interval_between_transcriptions_ms {
+		let remaining = state.cooldown_remaining(Duration::from_millis(min_interval));
+		if !remaining.is_zero() {
+			let _ = app.emit("throttled", remaining.as_millis() as u64);
+			update_tray_tooltip(app, "Cooling down...");
+			std::thread::sleep(remaining);
+			update_tray_tooltip(app, "Transcribing...");
+		}
+	}
+
+	let audio_secs = samples.len() as f64 / audio::TRANSCRIPTION_SAMPLE_RATE as f64;
+	let timeout = config.transcription_timeout(audio_secs);
+
+	let started = Instant::now();
+	let (result, timed_out) = {
+		let transcriber = state.transcriber.lock();
+		// Started once the lock is held, so waiting for another job doesn't count
+		let watchdog = state.start_watchdog(timeout);
+		let result = if let Some(ref t) = *transcriber {
+			if config.multi_language_detection && options.language == Some("auto") {
+				t.transcribe_multi_lang(
+					&samples,
+					&options,
+					&config.candidate_languages,
+					watchdog.cancel(),
+				)
+			} else {
+				t.transcribe_detailed(&samples, &options, watchdog.cancel())
+			}
+		} else {
+			Err(anyhow::anyhow!("No model loaded"))
+		};
+		(result, watchdog.finish())
+	};
+
+	let result = if timed_out {
+		Err(anyhow::anyhow!(
+			"Transcription timed out after {} seconds",
+			timeout.as_secs()
+		))
+	} else {
+		result
+	};
+
+	let result = result.map(|transcription| {
+		if let Some(recovery) = transcription.recovery {
+			let _ = app.emit("state-recovered", recovery);
+		}
+		if transcription.filtered_segments > 0 {
+			let _ = app.emit("segments-filtered", transcription.filtered_segments);
+		}
+		if lock_language
+			&& locked_language.is_none()
+			&& let Some(ref language) = transcription.language
+		{
+			state.set_locked_language(Some(language.clone()));
+			let _ = app.emit("language-locked", language);
+		}
+		if let Some(limit) = config.repetition_warning_ratio
+			&& let Some(ratio) = text_processing::repetition_ratio(&transcription.text)
+			&& ratio > limit
+		{
+			let _ = app.emit("possible-hallucination", ratio);
+		}
+		let language = transcription
+			.language
+			.as_deref()
+			.or(options.language)
+			.filter(|language| *language != "auto");
+
+		let mut text = apply_corrections(transcription.text, &config);
+		if config.auto_end_punctuation {
+			text = text_processing::ensure_end_punctuation(&text, language);
+		}
+		// Last of the transforms so none of them changes the casing back
+		text = text_processing::apply_acronyms(&text, &config.acronyms);
+		// Counted before tagging so the tag doesn't add a word to the stats
+		let words_text = text.clone();
+		// Tagged before it reaches the history and log so they carry it too
+		if config.tag_language
+			&& let Some(language) = language
+		{
+			text = text_processing::tag_language(&text, language);
+		}
+		(text, words_text, language.map(str::to_string))
+	});
+
+	match result {
+		Ok((text, words_text, language)) => {
+			state.record_transcription(
+				audio_secs,
+				&words_text,
+				started.elapsed().as_secs_f64(),
+			);
+
+			if !text.is_empty() {
+				let mut output = text_processing::format_output(&text, config.output_format);
+				if state.dictation_session.load(Ordering::SeqCst)
+					&& let Some(ref separator) = config.segment_separator
+				{
+					output.push_str(separator);
+				}
+				// The editor is already a review step of its own
+				if config.output_mode != OutputMode::Editor && config.needs_review(&output) {
+					// Waits in the background so recording can go on meanwhile
+					let app = app.clone();
+					std::thread::spawn(move || {
+						let state = app.state::<Arc<AppStateManager>>();
+						if await_review(&app, &state, &output) {
+							deliver_transcription(&app, &text, &output, language.as_deref());
+						}
+					});
+				} else {
+					deliver_transcription(app, &text, &output, language.as_deref());
+				}
+			} else if permissions::check_mic_permission() == MicPermission::Denied {
+				// Denied access records silence, which looks like an empty result
+				notify_mic_permission(app, MicPermission::Denied);
+			} else {
+				match config.on_empty_result {
+					EmptyResultAction::Notify => show_notification(
+						app,
+						NotificationKind::Info,
+						"No speech detected",
+						"Try speaking louder or closer to the microphone",
+					),
+					EmptyResultAction::RetryLouderHint => show_notification(
+						app,
+						NotificationKind::Info,
+						"No speech detected",
+						"Press the hotkey again and speak louder, or move closer to the microphone",
+					),
+					EmptyResultAction::Silent => {}
+				}
+			}
+
+			state.set_error(None);
+		}
+		Err(e) => {
+			end_dictation_session(app);
+			state.set_error(Some(e.to_string()));
+			let _ = app.emit("error", e.to_string());
+			show_notification(
+				app,
+				NotificationKind::Error,
+				"Transcription failed",
+				&e.to_string(),
+			);
+		}
+	}
+
+	state.mark_transcription_completed();
+	state.set_state(AppState::Idle);
+	let _ = app.emit("state-changed", AppState::Idle);
+	update_tray_tooltip(app, "Idle - Press F9 to record");
+
+	// Continuous mode goes straight on to the next segment
+	if state.dictation_session.load(Ordering::SeqCst) {
+		start_recording(app);
+	}
+}
+
+/// Stores, logs and outputs a finished transcription. `output` is `text`
+/// formatted for the clipboard, editor or typing.
+fn deliver_transcription(app: &AppHandle, text: &str, output: &str, language: Option<&str>) {
+	let state = app.state::<Arc<AppStateManager>>();
+	let config = state.get_config();
+
+	state.set_last_transcription(text.to_string());
+	state.add_history(text);
+	append_transcription_log(&state, &config, text, language);
+	if let Some(ref command) = config.post_command {
+		run_post_command(app, command, text);
+	}
+
+	match config.output_mode {
+		OutputMode::Clipboard => {
+			if config.auto_copy {
+				write_clipboard(app, output, config.clipboard_format);
+			}
+		}
+		OutputMode::Editor => show_editor(app, output),
+		OutputMode::TargetApp => {
+			// Without a target, or when it isn't found, the active window gets it
+			if let Some(ref target) = config.target_app
+				&& let Err(e) = focus::focus_app(target)
+			{
+				eprintln!("Failed to focus {}: {}", target, e);
+			}
+			type_output(app, &config, output);
+		}
+		OutputMode::Type => type_output(app, &config, output),
+	}
+
+	let _ = app.emit("transcription", text);
+	if config.split_sentences {
+		emit_sentences(app, text, Duration::from_millis(config.sentence_delay_ms));
+	}
+
+	if config.show_notifications {
+		let preview = if text.len() > 50 {
+			format!("{}...", &text[..50])
+		} else {
+			text.to_string()
+		};
+		let actions: &[NotificationAction] = if config.auto_copy {
+			&[]
+		} else {
+			&[NotificationAction::CopyTranscription]
+		};
+		show_notification_with_actions(
+			app,
+			NotificationKind::Info,
+			"Transcribed",
+			&preview,
+			actions,
+		);
+	}
+}
+
+/// Types `output` into the focused window, leaving it on the clipboard
+/// if typing fails so it isn't lost
+fn type_output(app: &AppHandle, config: &Config, output: &str) {
+	let typed = typing::type_text(
+		output,
+		Duration::from_millis(config.type_start_delay_ms),
+		Duration::from_millis(config.type_char_delay_ms),
+	);
+	if let Err(e) = typed {
+		eprintln!("{:#}", e);
+		write_clipboard(app, output, config.clipboard_format);
+		show_notification(
+			app,
+			NotificationKind::Error,
+			"Typing Failed",
+			"The transcription was copied to the clipboard instead",
+		);
+	}
+}
+
+#[tauri::command]
+fn check_mic_permission() -> MicPermission {
+	permissions::check_mic_permission()
+}
+
+/// Tells the user how to grant microphone access, if it's missing
+fn notify_mic_permission(app: &AppHandle, permission: MicPermission) {
+	match permission {
+		MicPermission::Granted => {}
+		MicPermission::Denied => show_notification(
+			app,
+			NotificationKind::Error,
+			"Microphone access denied",
+			"Allow Shy to Text in System Settings > Privacy & Security > Microphone",
+		),
+		MicPermission::Undetermined => show_notification(
+			app,
+			NotificationKind::Info,
+			"Microphone access",
+			"You'll be asked to allow microphone access on your first recording",
+		),
+	}
+}
+
+/// One sentence of a transcription, emitted as "transcription-sentence"
+#[derive(Debug, Clone, serde::Serialize)]
+struct TranscriptionSentence {
+	index: usize,
+	total: usize,
+	text: String,
+}
+
+/// Emits each sentence of `text` separately, `delay` apart
+fn emit_sentences(app: &AppHandle, text: &str, delay: Duration) {
+	let sentences = text_processing::split_sentences(text);
+	let app = app.clone();
+
+	std::thread::spawn(move || {
+		let total = sentences.len();
+		for (index, text) in sentences.into_iter().enumerate() {
+			if index > 0 {
+				std::thread::sleep(delay);
+			}
+			let _ = app.emit("transcription-sentence", TranscriptionSentence { index, total, text });
+		}
+	});
+}
+
+/// Held while a review is open so later transcriptions wait their turn
+static REVIEW_QUEUE: Mutex<()> = Mutex::new(());
+
+/// Shows `text` in the main window as "review-transcription" and waits
+/// for `resolve_review`. Returns whether it was accepted.
+fn await_review(app: &AppHandle, state: &AppStateManager, text: &str) -> bool {
+	let _turn = REVIEW_QUEUE.lock();
+	let timeout = Duration::from_secs(state.get_config().review_timeout_secs);
+	let (sender, receiver) = mpsc::channel();
+	*state.pending_review.lock() = Some(sender);
+
+	show_main_window(app);
+	let _ = app.emit("review-transcription", text);
+
+	let accepted = receiver.recv_timeout(timeout).unwrap_or(false);
+	// Drop the sender if the review timed out so a late answer is rejected
+	state.pending_review.lock().take();
+	let _ = app.emit("review-closed", accepted);
+	accepted
+}
+
+/// Tells the user when a model load didn't go exactly as requested
+fn report_load_outcome(app: &AppHandle, outcome: &ModelLoadOutcome) {
+	if outcome.gpu_fallback {
+		show_notification(
+			app,
+			NotificationKind::Error,
+			"GPU Unavailable",
+			"Failed to use GPU acceleration, using CPU instead",
+		);
+		let _ = app.emit("gpu-fallback", ());
+	}
+
+	if let Some(ref fallback) = outcome.fallback_model {
+		report_model_fallback(app, fallback);
+	}
+}
+
+/// How long after the main window loses focus a tray click still treats
+/// it as in front
+const TRAY_CLICK_GRACE: Duration = Duration::from_millis(500);
+
+/// How often the power source is checked for `adaptive_power`
+const POWER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Emitted as "power-model-switched" when the power source changes the
+/// loaded model
+#[derive(Debug, Clone, serde::Serialize)]
+struct PowerModelSwitch {
+	on_battery: bool,
+	model_path: String,
+}
+
+/// Watches the power source and loads the low-power model on battery and
+/// the configured one back on AC, when `adaptive_power` is enabled
+fn start_power_monitor(app: AppHandle) {
+	std::thread::spawn(move || {
+		let mut applied: Option<bool> = None;
+		loop {
+			let state = app.state::<Arc<AppStateManager>>();
+			let config = state.get_config();
+			if !config.adaptive_power {
+				applied = None;
+			} else if let Ok(on_battery) = power::on_battery()
+				&& applied != Some(on_battery)
+				// Don't swap the model out from under a recording
+				&& state.get_state() == AppState::Idle
+			{
+				applied = Some(on_battery);
+				if let Some(model_path) = config.model_for_power(on_battery) {
+					let loaded = state.loaded_model().map(|model| model.path);
+					if loaded.as_deref() != Some(model_path) {
+						match state.load_model_temporarily(model_path) {
+							Ok(outcome) => {
+								report_load_outcome(&app, &outcome);
+								let _ = app.emit(
+									"power-model-switched",
+									PowerModelSwitch {
+										on_battery,
+										model_path: model_path.to_string(),
+									},
+								);
+							}
+							Err(e) => {
+								// Tried again on the next check
+								applied = None;
+								eprintln!("Failed to switch model for power source: {}", e);
+							}
+						}
+					}
+				}
+			}
+			std::thread::sleep(POWER_POLL_INTERVAL);
+		}
+	});
+}
+
+fn report_model_fallback(app: &AppHandle, fallback: &str) {
+	let name = Path::new(fallback)
+		.file_name()
+		.map(|n| n.to_string_lossy().to_string())
+		.unwrap_or_else(|| fallback.to_string());
+	show_notification(
+		app,
+		NotificationKind::Error,
+		"Fallback Model Active",
+		&format!("The selected model failed to load, using {} instead", name),
+	);
+	let _ = app.emit("model-fallback", fallback);
+}
+
+/// Registers or unregisters the app as an OS login item
+fn apply_autostart(app: &AppHandle, enabled: bool) -> Result<(), String> {
+	let autolaunch = app.autolaunch();
+	let result = if enabled {
+		autolaunch.enable()
+	} else {
+		autolaunch.disable()
+	};
+	result.map_err(|e| format!("Failed to update autostart: {}", e))
+}
+
+/// Saves the main window's position and size for the next time it's shown
+fn save_window_geometry(window: &WebviewWindow) -> anyhow::Result<()> {
+	// A minimized window reports a parked position that's useless to restore
+	if window.is_minimized()? {
+		return Ok(());
+	}
+	let position = window.outer_position()?;
+	let size = window.inner_size()?;
+	window_state::WindowGeometry {
+		x: position.x,
+		y: position.y,
+		width: size.width,
+		height: size.height,
+	}
+	.save()
+}
+
+/// Moves the main window back to its saved geometry, unless that would
+/// leave it off-screen. Returns whether it was restored.
+fn restore_window_geometry(window: &WebviewWindow) -> anyhow::Result<bool> {
+	let Some(geometry) = window_state::WindowGeometry::load()? else {
+		return Ok(false);
+	};
+
+	let monitors: Vec<_> = window
+		.available_monitors()?
+		.iter()
+		.map(|monitor| window_state::MonitorArea {
+			x: monitor.position().x,
+			y: monitor.position().y,
+			width: monitor.size().width,
+			height: monitor.size().height,
+		})
+		.collect();
+	if !geometry.is_visible_on(&monitors) {
+		return Ok(false);
+	}
+
+	window.set_size(PhysicalSize::new(geometry.width, geometry.height))?;
+	window.set_position(PhysicalPosition::new(geometry.x, geometry.y))?;
+	Ok(true)
+}
+
+/// Shows and focuses the main window where it was last left
+fn show_main_window(app: &AppHandle) {
+	let Some(window) = app.get_webview_window("main") else {
+		return;
+	};
+	// Hiding keeps its place, so the saved geometry is only restored once
+	// at startup
+	let state = app.state::<Arc<AppStateManager>>();
+	if !state.get_config().restore_previous_focus || window.is_focused().unwrap_or(false) {
+		let _ = window.show();
+		let _ = window.set_focus();
+		return;
+	}
+
+	// Looked up before showing, so it finds the window to go back to, and
+	// off the main thread since it can wait on a helper process
+	let app = app.clone();
+	std::thread::spawn(move || {
+		let state = app.state::<Arc<AppStateManager>>();
+		*state.previous_focus.lock() = focus::active_window();
+		let _ = window.show();
+		let _ = window.set_focus();
+	});
+}
+
+/// Hides the main window and gives focus back to the window that had it
+/// before it was shown
+fn hide_main_window(app: &AppHandle) {
+	let Some(window) = app.get_webview_window("main") else {
+		return;
+	};
+	if let Err(e) = save_window_geometry(&window) {
+		eprintln!("Failed to save window state: {}", e);
+	}
+	let _ = window.hide();
+
+	let state = app.state::<Arc<AppStateManager>>();
+	if !state.get_config().restore_previous_focus {
+		return;
+	}
+	if let Some(previous) = state.previous_focus.lock().take() {
+		// Activating another app can take a while on macOS
+		std::thread::spawn(move || {
+			if let Err(e) = focus::restore_focus(&previous) {
+				eprintln!("Failed to restore focus: {}", e);
+			}
+		});
+	}
+}
+
+/// Whether the main window is visible and in front. Clicking the tray icon
+/// can take focus from it first, so losing focus just before still counts.
+fn main_window_in_front(app: &AppHandle) -> bool {
+	let Some(window) = app.get_webview_window("main") else {
+		return false;
+	};
+	if !window.is_visible().unwrap_or(false) {
+		return false;
+	}
+	let blurred = *app.state::<Arc<AppStateManager>>().main_window_blurred.lock();
+	window.is_focused().unwrap_or(false)
+		|| blurred.is_some_and(|blurred| blurred.elapsed() < TRAY_CLICK_GRACE)
+}
+
+#[tauri::command]
+fn hide_window(app: AppHandle) {
+	hide_main_window(&app);
 }
 
 #[tauri::command]
-fn get_available_models() -> Result<Vec<ModelInfo>, String> {
-	Config::detect_models().map_err(|e| e.to_string())
+fn save_window_state(app: AppHandle) -> Result<(), String> {
+	let window = app
+		.get_webview_window("main")
+		.ok_or_else(|| "Main window not found".to_string())?;
+	save_window_geometry(&window).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn load_model(
-	app: AppHandle,
-	state: tauri::State<Arc<AppStateManager>>,
-	model_path: String,
-) -> Result<(), String> {
-	let fell_back = state.load_model(&model_path).map_err(|e| e.to_string())?;
+fn restore_window_state(app: AppHandle) -> Result<bool, String> {
+	let window = app
+		.get_webview_window("main")
+		.ok_or_else(|| "Main window not found".to_string())?;
+	restore_window_geometry(&window).map_err(|e| e.to_string())
+}
 
-	if fell_back {
-		show_notification(
-			&app,
-			"GPU Unavailable",
-			"Failed to use GPU acceleration, using CPU instead",
-		);
-		let _ = app.emit("gpu-fallback", ());
+/// Opens the editor window, or focuses it if already open, with `text`
+fn show_editor(app: &AppHandle, text: &str) {
+	if let Some(window) = app.get_webview_window("editor") {
+		let _ = window.show();
+		let _ = window.set_focus();
+		let _ = app.emit_to("editor", "editor-text", text);
+		return;
 	}
 
-	Ok(())
+	// The page isn't listening yet, so hand it the text before it loads
+	let init_script = format!(
+		"window.__EDITOR_TEXT__ = {};",
+		serde_json::to_string(text).unwrap_or_else(|_| "null".to_string())
+	);
+	let result = WebviewWindowBuilder::new(app, "editor", WebviewUrl::App("index.html#editor".into()))
+		.initialization_script(&init_script)
+		.title("Shy to Text - Editor")
+		.inner_size(420.0, 320.0)
+		.always_on_top(true)
+		.build();
+
+	if let Err(e) = result {
+		eprintln!("Failed to open editor window: {}", e);
+		let _ = app.clipboard().write_text(text);
+	}
 }
 
-#[tauri::command]
-fn has_model_loaded(state: tauri::State<Arc<AppStateManager>>) -> bool {
-	state.has_model()
+/// Copies a transcription without trailing whitespace, adding an HTML
+/// version when `format` asks for it
+fn write_clipboard(app: &AppHandle, text: &str, format: ClipboardFormat) {
+	let text = text.trim_end();
+	let result = match format {
+		ClipboardFormat::Plain => app.clipboard().write_text(text),
+		ClipboardFormat::Html => app
+			.clipboard()
+			.write_html(text_processing::to_clipboard_html(text), Some(text)),
+	};
+	if let Err(e) = result {
+		eprintln!("Failed to write to the clipboard: {}", e);
+	}
 }
 
-#[tauri::command]
-fn is_model_multilingual(state: tauri::State<Arc<AppStateManager>>) -> bool {
-	state.is_multilingual()
+/// Fixes misheard terms using the user's correction wordlist, if configured
+fn apply_corrections(text: String, config: &Config) -> String {
+	let path = match config.correction_wordlist_path() {
+		Ok(Some(path)) => path,
+		Ok(None) => return text,
+		Err(e) => {
+			eprintln!("Failed to resolve correction wordlist: {}", e);
+			return text;
+		}
+	};
+
+	match text_processing::load_wordlist(&path) {
+		Ok(wordlist) => text_processing::correct_terms(&text, &wordlist),
+		Err(e) => {
+			eprintln!("{}", e);
+			text
+		}
+	}
 }
 
-#[tauri::command]
-fn get_last_transcription(state: tauri::State<Arc<AppStateManager>>) -> String {
-	state.get_last_transcription()
+/// Appends the transcription to the configured log file using `output_template`,
+/// with `{lang}` the language the audio was transcribed as, empty when unknown
+fn append_transcription_log(state: &AppStateManager, config: &Config, text: &str, language: Option<&str>) {
+	let path = match config.transcription_log_path() {
+		Ok(Some(path)) => path,
+		Ok(None) => return,
+		Err(e) => {
+			eprintln!("Failed to resolve transcription log: {}", e);
+			return;
+		}
+	};
+
+	let time = history::format_timestamp(history::now_secs());
+	let model = state
+		.transcriber
+		.lock()
+		.as_ref()
+		.and_then(|t| Path::new(t.model_path()).file_stem().map(|s| s.to_string_lossy().to_string()))
+		.unwrap_or_default();
+	let values = [
+		("text", text),
+		("lang", language.unwrap_or_default()),
+		("time", time.as_str()),
+		("model", model.as_str()),
+	];
+	let line = text_processing::render_template(&config.output_template, &values);
+
+	let result = std::fs::OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(&path)
+		.and_then(|mut file| writeln!(file, "{}", line.replace(['\r', '\n'], " ")));
+	if let Err(e) = result {
+		eprintln!("Failed to write transcription log {}: {}", path.display(), e);
+	}
 }
 
-#[tauri::command]
-fn get_last_error(state: tauri::State<Arc<AppStateManager>>) -> Option<String> {
-	state.get_error()
+/// Writes a recording to the recordings directory and remembers its path
+fn save_recording(state: &AppStateManager, samples: &[f32]) {
+	let name = history::format_timestamp(history::now_secs()).replace(':', "-");
+	let result = Config::recordings_dir().and_then(|dir| {
+		let path = dir.join(format!("{}{}.wav", audio::RECORDING_FILE_PREFIX, name));
+		audio::write_wav_file(&path, samples)?;
+		if let Some(keep) = state.get_config().max_saved_recordings
+			&& let Err(e) = audio::prune_recordings(&dir, keep)
+		{
+			eprintln!("Failed to prune old recordings: {:#}", e);
+		}
+		Ok(path)
+	});
+
+	match result {
+		Ok(path) => *state.last_recording_path.lock() = Some(path),
+		Err(e) => eprintln!("Failed to save recording: {:#}", e),
+	}
 }
 
-#[tauri::command]
-fn get_models_directory() -> Result<String, String> {
-	Config::models_dir()
-		.map(|p| p.to_string_lossy().to_string())
-		.map_err(|e| e.to_string())
+/// Runs `post_command` for a transcription in the background, emitting
+/// `post-command-failed` if it can't start or exits unsuccessfully
+fn run_post_command(app: &AppHandle, command: &str, text: &str) {
+	let mut args = text_processing::split_command_line(command);
+	if args.is_empty() {
+		return;
+	}
+	let program = args.remove(0);
+
+	let uses_placeholder = args.iter().any(|arg| arg.contains("{text}"));
+	let args: Vec<String> = args
+		.iter()
+		.map(|arg| arg.replace("{text}", text))
+		.collect();
+
+	let app = app.clone();
+	let text = text.to_string();
+	std::thread::spawn(move || {
+		let result = std::process::Command::new(&program)
+			.args(&args)
+			.stdin(std::process::Stdio::piped())
+			.spawn()
+			.and_then(|mut child| {
+				if let Some(mut stdin) = child.stdin.take()
+					&& !uses_placeholder
+				{
+					// The command may exit without reading, that's not a failure
+					let _ = stdin.write_all(text.as_bytes());
+				}
+				child.wait()
+			});
+
+		let error = match result {
+			Ok(status) if status.success() => return,
+			Ok(status) => format!("{} exited with {}", program, status),
+			Err(e) => format!("Failed to run {}: {}", program, e),
+		};
+		eprintln!("{}", error);
+		let _ = app.emit("post-command-failed", error);
+	});
 }
 
-#[tauri::command]
-fn get_input_devices() -> Result<Vec<String>, String> {
-	audio::list_input_devices().map_err(|e| e.to_string())
+fn show_notification(app: &AppHandle, kind: NotificationKind, title: &str, body: &str) {
+	let config = app.state::<Arc<AppStateManager>>().get_config();
+	if !kind.allowed_by(config.effective_notification_level()) {
+		return;
+	}
+
+	show_plain_notification(app, title, body, config.notification_sound);
 }
 
-#[tauri::command]
-fn get_supported_languages() -> Vec<LanguageInfo> {
-	transcribe::get_supported_languages()
+/// Payload of `notification-toast`, shown inside the window when system
+/// notifications don't work
+#[derive(Clone, serde::Serialize)]
+struct NotificationToast {
+	title: String,
+	body: String,
 }
 
-#[tauri::command]
-fn get_gpu_devices() -> Vec<gpu::GpuDevice> {
-	gpu::get_gpu_devices()
+/// Asks the window to show a notification itself
+fn show_toast(app: &AppHandle, title: &str, body: &str) {
+	let toast = NotificationToast {
+		title: title.to_string(),
+		body: body.to_string(),
+	};
+	let _ = app.emit("notification-toast", toast);
 }
 
-fn toggle_recording(app: &AppHandle) {
+/// Falls back to in-window toasts for this and every later notification
+/// of the session
+fn notification_failed(app: &AppHandle, title: &str, body: &str, error: impl std::fmt::Display) {
+	eprintln!("Failed to show notification: {}", error);
 	let state = app.state::<Arc<AppStateManager>>();
-	let current_state = state.get_state();
+	state.notifications_working.store(false, Ordering::SeqCst);
+	show_toast(app, title, body);
+}
 
-	match current_state {
-		AppState::Idle => {
-			if !state.has_model() {
-				state.set_error(Some("No model loaded".to_string()));
-				let _ = app.emit("error", "No model loaded. Please load a Whisper model first.");
-				show_notification(app, "Error", "No model loaded");
-				return;
-			}
+/// Uses notify-rust directly, which reports a missing notification daemon
+/// and can silence the sound through a hint
+#[cfg(target_os = "linux")]
+fn show_plain_notification(app: &AppHandle, title: &str, body: &str, sound: bool) {
+	if !app.state::<Arc<AppStateManager>>().notifications_working.load(Ordering::SeqCst) {
+		show_toast(app, title, body);
+		return;
+	}
 
-			match RecordingSession::start() {
-				Ok(session) => {
-					*RECORDING_SESSION.lock() = Some(session);
-					state.set_state(AppState::Recording);
-					state.set_error(None);
-					let _ = app.emit("state-changed", AppState::Recording);
-					update_tray_tooltip(app, "Recording...");
-				}
-				Err(e) => {
-					state.set_error(Some(e.to_string()));
-					let _ = app.emit("error", e.to_string());
-					show_notification(app, "Error", &format!("Failed to start recording: {}", e));
-				}
-			}
-		}
-		AppState::Recording => {
-			state.set_state(AppState::Transcribing);
-			let _ = app.emit("state-changed", AppState::Transcribing);
-			update_tray_tooltip(app, "Transcribing...");
+	let mut notification = notify_rust::Notification::new();
+	notification.summary(title).body(body).auto_icon();
+	if !sound {
+		notification.hint(notify_rust::Hint::SuppressSound(true));
+	}
+	if let Err(e) = notification.show() {
+		notification_failed(app, title, body, e);
+	}
+}
 
-			let session = RECORDING_SESSION.lock().take();
+#[cfg(not(target_os = "linux"))]
+fn show_plain_notification(app: &AppHandle, title: &str, body: &str, _sound: bool) {
+	if !app.state::<Arc<AppStateManager>>().notifications_working.load(Ordering::SeqCst) {
+		show_toast(app, title, body);
+		return;
+	}
 
-			if let Some(session) = session {
-				match session.stop() {
-					Ok(samples) => {
-						let app_clone = app.clone();
-						std::thread::spawn(move || {
-							process_transcription(&app_clone, samples);
-						});
-					}
-					Err(e) => {
-						state.set_state(AppState::Idle);
-						state.set_error(Some(e.to_string()));
-						let _ = app.emit("state-changed", AppState::Idle);
-						let _ = app.emit("error", e.to_string());
-						show_notification(app, "Error", &format!("Recording failed: {}", e));
-						update_tray_tooltip(app, "Idle - Press F9 to record");
-					}
-				}
-			} else {
-				state.set_state(AppState::Idle);
-				let _ = app.emit("state-changed", AppState::Idle);
-				update_tray_tooltip(app, "Idle - Press F9 to record");
+	if let Err(e) = app.notification().builder().title(title).body(body).show() {
+		notification_failed(app, title, body, e);
+	}
+}
+
+/// Shows a notification with action buttons. Falls back to a plain
+/// notification when the notification daemon can't display actions.
+#[cfg(target_os = "linux")]
+fn show_notification_with_actions(
+	app: &AppHandle,
+	kind: NotificationKind,
+	title: &str,
+	body: &str,
+	actions: &[NotificationAction],
+) {
+	let config = app.state::<Arc<AppStateManager>>().get_config();
+	if !kind.allowed_by(config.effective_notification_level()) {
+		return;
+	}
+
+	let supports_actions = notify_rust::get_capabilities()
+		.map(|caps| caps.iter().any(|c| c == "actions"))
+		.unwrap_or(false);
+
+	let notifications_working = app
+		.state::<Arc<AppStateManager>>()
+		.notifications_working
+		.load(Ordering::SeqCst);
+	if actions.is_empty() || !supports_actions || !notifications_working {
+		show_plain_notification(app, title, body, config.notification_sound);
+		return;
+	}
+
+	let mut notification = notify_rust::Notification::new();
+	notification.summary(title).body(body).auto_icon();
+	if !config.notification_sound {
+		notification.hint(notify_rust::Hint::SuppressSound(true));
+	}
+	for action in actions {
+		notification.action(action.id(), action.label());
+	}
+
+	let app = app.clone();
+	let actions = actions.to_vec();
+	let (title, body) = (title.to_string(), body.to_string());
+	std::thread::spawn(move || match notification.show() {
+		Ok(handle) => handle.wait_for_action(|id| {
+			if let Some(action) = actions.iter().find(|a| a.id() == id) {
+				handle_notification_action(&app, *action);
 			}
+		}),
+		Err(e) => notification_failed(&app, &title, &body, e),
+	});
+}
+
+#[cfg(not(target_os = "linux"))]
+fn show_notification_with_actions(
+	app: &AppHandle,
+	kind: NotificationKind,
+	title: &str,
+	body: &str,
+	_actions: &[NotificationAction],
+) {
+	show_notification(app, kind, title, body);
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn handle_notification_action(app: &AppHandle, action: NotificationAction) {
+	match action {
+		NotificationAction::CopyTranscription => {
+			let state = app.state::<Arc<AppStateManager>>();
+			let format = state.get_config().clipboard_format;
+			write_clipboard(app, &state.get_last_transcription(), format);
 		}
-		AppState::Transcribing => {}
 	}
 }
 
-fn process_transcription(app: &AppHandle, samples: Vec<f32>) {
-	let state = app.state::<Arc<AppStateManager>>();
-	let config = state.get_config();
+fn update_tray_tooltip(app: &AppHandle, tooltip: &str) {
+	if let Some(tray) = app.tray_by_id("main-tray") {
+		let _ = tray.set_tooltip(Some(tooltip));
+	}
+}
 
-	let language = if config.language == "auto" {
-		None
-	} else {
-		Some(config.language.as_str())
-	};
+/// Modifier tokens accepted in hotkey strings, canonical spelling first
+const HOTKEY_MODIFIERS: &[(&[&str], Modifiers)] = &[
+	(&["Ctrl", "Control"], Modifiers::CONTROL),
+	(&["Alt"], Modifiers::ALT),
+	(&["Shift"], Modifiers::SHIFT),
+	(&["Super", "Meta", "Win"], Modifiers::SUPER),
+];
 
-	let result = {
-		let transcriber = state.transcriber.lock();
-		if let Some(ref t) = *transcriber {
-			t.transcribe(&samples, language)
-		} else {
-			Err(anyhow::anyhow!("No model loaded"))
-		}
-	};
+/// Keys that can be bound, the single source for parsing and for the
+/// settings UI
+const HOTKEY_KEYS: &[(&str, Code)] = &[
+	("F1", Code::F1),
+	("F2", Code::F2),
+	("F3", Code::F3),
+	("F4", Code::F4),
+	("F5", Code::F5),
+	("F6", Code::F6),
+	("F7", Code::F7),
+	("F8", Code::F8),
+	("F9", Code::F9),
+	("F10", Code::F10),
+	("F11", Code::F11),
+	("F12", Code::F12),
+	("A", Code::KeyA),
+	("B", Code::KeyB),
+	("C", Code::KeyC),
+	("D", Code::KeyD),
+	("E", Code::KeyE),
+	("F", Code::KeyF),
+	("G", Code::KeyG),
+	("H", Code::KeyH),
+	("I", Code::KeyI),
+	("J", Code::KeyJ),
+	("K", Code::KeyK),
+	("L", Code::KeyL),
+	("M", Code::KeyM),
+	("N", Code::KeyN),
+	("O", Code::KeyO),
+	("P", Code::KeyP),
+	("Q", Code::KeyQ),
+	("R", Code::KeyR),
+	("S", Code::KeyS),
+	("T", Code::KeyT),
+	("U", Code::KeyU),
+	("V", Code::KeyV),
+	("W", Code::KeyW),
+	("X", Code::KeyX),
+	("Y", Code::KeyY),
+	("Z", Code::KeyZ),
+	("0", Code::Digit0),
+	("1", Code::Digit1),
+	("2", Code::Digit2),
+	("3", Code::Digit3),
+	("4", Code::Digit4),
+	("5", Code::Digit5),
+	("6", Code::Digit6),
+	("7", Code::Digit7),
+	("8", Code::Digit8),
+	("9", Code::Digit9),
+	("Space", Code::Space),
+];
 
-	match result {
-		Ok(text) => {
-			if !text.is_empty() {
-				state.set_last_transcription(text.clone());
+/// A token accepted by `parse_hotkey`
+#[derive(Debug, Clone, serde::Serialize)]
+struct KeyInfo {
+	token: &'static str,
+	/// Other spellings accepted for the same key
+	aliases: &'static [&'static str],
+	modifier: bool,
+}
 
-				let config = state.get_config();
-				if config.auto_copy {
-					let _ = app.clipboard().write_text(&text);
-				}
+/// Lists every modifier and key a hotkey can be built from
+#[tauri::command]
+fn get_bindable_keys() -> Vec<KeyInfo> {
+	let modifiers = HOTKEY_MODIFIERS.iter().map(|(tokens, _)| KeyInfo {
+		token: tokens[0],
+		aliases: &tokens[1..],
+		modifier: true,
+	});
+	let keys = HOTKEY_KEYS.iter().map(|(token, _)| KeyInfo {
+		token,
+		aliases: &[],
+		modifier: false,
+	});
 
-				let _ = app.emit("transcription", &text);
+	modifiers.chain(keys).collect()
+}
 
-				if config.show_notifications {
-					let preview = if text.len() > 50 {
-						format!("{}...", &text[..50])
-					} else {
-						text.clone()
-					};
-					show_notification(app, "Transcribed", &preview);
-				}
-			} else {
-				show_notification(
-					app,
-					"No speech detected",
-					"Try speaking louder or closer to the microphone",
-				);
-			}
+/// A suggested recording hotkey, as defined in `HOTKEY_PRESETS`
+struct HotkeyPresetDef {
+	id: &'static str,
+	hotkey: &'static str,
+	/// What the combination already does on each platform, if anything
+	macos_conflict: Option<&'static str>,
+	windows_conflict: Option<&'static str>,
+	linux_conflict: Option<&'static str>,
+}
 
-			state.set_error(None);
-		}
-		Err(e) => {
-			state.set_error(Some(e.to_string()));
-			let _ = app.emit("error", e.to_string());
-			show_notification(app, "Transcription failed", &e.to_string());
-		}
-	}
+const HOTKEY_PRESETS: &[HotkeyPresetDef] = &[
+	HotkeyPresetDef {
+		id: "f9",
+		hotkey: "F9",
+		macos_conflict: Some("Needs Fn unless function keys are set to act as standard keys"),
+		windows_conflict: None,
+		linux_conflict: None,
+	},
+	HotkeyPresetDef {
+		id: "ctrl_shift_space",
+		hotkey: "Ctrl+Shift+Space",
+		macos_conflict: None,
+		windows_conflict: None,
+		linux_conflict: None,
+	},
+	HotkeyPresetDef {
+		id: "ctrl_alt_d",
+		hotkey: "Ctrl+Alt+D",
+		macos_conflict: None,
+		windows_conflict: None,
+		linux_conflict: Some("Shows the desktop on some GNOME setups"),
+	},
+	HotkeyPresetDef {
+		id: "alt_space",
+		hotkey: "Alt+Space",
+		macos_conflict: Some("Types a non-breaking space while the app is running"),
+		windows_conflict: Some("Replaces the window menu and PowerToys Run"),
+		linux_conflict: Some("Opens the window menu or KRunner on most desktops"),
+	},
+];
 
-	state.set_state(AppState::Idle);
-	let _ = app.emit("state-changed", AppState::Idle);
-	update_tray_tooltip(app, "Idle - Press F9 to record");
+/// A suggested recording hotkey with what it may clash with on this platform
+#[derive(Debug, Clone, serde::Serialize)]
+struct HotkeyPreset {
+	id: &'static str,
+	hotkey: &'static str,
+	label: String,
+	conflict: Option<&'static str>,
 }
 
-fn show_notification(app: &AppHandle, title: &str, body: &str) {
-	let _ = app.notification().builder().title(title).body(body).show();
+#[tauri::command]
+fn get_hotkey_presets() -> Vec<HotkeyPreset> {
+	HOTKEY_PRESETS
+		.iter()
+		.map(|preset| {
+			let conflict = if cfg!(target_os = "macos") {
+				preset.macos_conflict
+			} else if cfg!(target_os = "windows") {
+				preset.windows_conflict
+			} else {
+				preset.linux_conflict
+			};
+			HotkeyPreset {
+				id: preset.id,
+				hotkey: preset.hotkey,
+				// In the platform's own notation, e.g. ⌃⇧Space on macOS
+				label: parse_hotkey(preset.hotkey)
+					.map_or_else(|| preset.hotkey.to_string(), |shortcut| format_shortcut(&shortcut)),
+				conflict,
+			}
+		})
+		.collect()
 }
 
-fn update_tray_tooltip(app: &AppHandle, tooltip: &str) {
-	if let Some(tray) = app.tray_by_id("main-tray") {
-		let _ = tray.set_tooltip(Some(tooltip));
+/// Makes a preset the recording hotkey after checking it's free.
+/// Returns the hotkey.
+#[tauri::command]
+fn apply_hotkey_preset(
+	app: AppHandle,
+	state: tauri::State<Arc<AppStateManager>>,
+	id: String,
+) -> Result<String, String> {
+	let preset = HOTKEY_PRESETS
+		.iter()
+		.find(|preset| preset.id == id)
+		.ok_or_else(|| format!("Unknown hotkey preset: {}", id))?;
+	validate_hotkey(app.clone(), preset.hotkey.to_string())?;
+
+	let old_config = state.get_config();
+	let mut config = old_config.clone();
+	config.hotkey = preset.hotkey.to_string();
+
+	// Registered before saving, and rolled back if either step fails, so the
+	// saved hotkey is always the one that works
+	let _ = app.global_shortcut().unregister_all();
+	let applied = setup_global_shortcuts(&app, &config)
+		.and_then(|()| state.update_config(config.clone()).map_err(|e| e.to_string()));
+	if let Err(e) = applied {
+		restore_global_shortcuts(&app, &old_config);
+		return Err(e);
 	}
+
+	Ok(config.hotkey)
 }
 
 fn parse_hotkey(hotkey: &str) -> Option<Shortcut> {
-	let parts: Vec<&str> = hotkey.split('+').map(|s| s.trim()).collect();
 	let mut modifiers = Modifiers::empty();
 	let mut key_code = None;
 
-	for part in parts {
-		match part.to_uppercase().as_str() {
-			"CTRL" | "CONTROL" => modifiers |= Modifiers::CONTROL,
-			"ALT" => modifiers |= Modifiers::ALT,
-			"SHIFT" => modifiers |= Modifiers::SHIFT,
-			"SUPER" | "META" | "WIN" => modifiers |= Modifiers::SUPER,
-			"F1" => key_code = Some(Code::F1),
-			"F2" => key_code = Some(Code::F2),
-			"F3" => key_code = Some(Code::F3),
-			"F4" => key_code = Some(Code::F4),
-			"F5" => key_code = Some(Code::F5),
-			"F6" => key_code = Some(Code::F6),
-			"F7" => key_code = Some(Code::F7),
-			"F8" => key_code = Some(Code::F8),
-			"F9" => key_code = Some(Code::F9),
-			"F10" => key_code = Some(Code::F10),
-			"F11" => key_code = Some(Code::F11),
-			"F12" => key_code = Some(Code::F12),
-			"A" => key_code = Some(Code::KeyA),
-			"B" => key_code = Some(Code::KeyB),
-			"C" => key_code = Some(Code::KeyC),
-			"D" => key_code = Some(Code::KeyD),
-			"E" => key_code = Some(Code::KeyE),
-			"F" => key_code = Some(Code::KeyF),
-			"G" => key_code = Some(Code::KeyG),
-			"H" => key_code = Some(Code::KeyH),
-			"I" => key_code = Some(Code::KeyI),
-			"J" => key_code = Some(Code::KeyJ),
-			"K" => key_code = Some(Code::KeyK),
-			"L" => key_code = Some(Code::KeyL),
-			"M" => key_code = Some(Code::KeyM),
-			"N" => key_code = Some(Code::KeyN),
-			"O" => key_code = Some(Code::KeyO),
-			"P" => key_code = Some(Code::KeyP),
-			"Q" => key_code = Some(Code::KeyQ),
-			"R" => key_code = Some(Code::KeyR),
-			"S" => key_code = Some(Code::KeyS),
-			"T" => key_code = Some(Code::KeyT),
-			"U" => key_code = Some(Code::KeyU),
-			"V" => key_code = Some(Code::KeyV),
-			"W" => key_code = Some(Code::KeyW),
-			"X" => key_code = Some(Code::KeyX),
-			"Y" => key_code = Some(Code::KeyY),
-			"Z" => key_code = Some(Code::KeyZ),
-			"0" => key_code = Some(Code::Digit0),
-			"1" => key_code = Some(Code::Digit1),
-			"2" => key_code = Some(Code::Digit2),
-			"3" => key_code = Some(Code::Digit3),
-			"4" => key_code = Some(Code::Digit4),
-			"5" => key_code = Some(Code::Digit5),
-			"6" => key_code = Some(Code::Digit6),
-			"7" => key_code = Some(Code::Digit7),
-			"8" => key_code = Some(Code::Digit8),
-			"9" => key_code = Some(Code::Digit9),
-			"SPACE" => key_code = Some(Code::Space),
-			_ => {}
+	for part in hotkey.split('+').map(|s| s.trim()) {
+		let modifier = HOTKEY_MODIFIERS
+			.iter()
+			.find(|(tokens, _)| tokens.iter().any(|t| t.eq_ignore_ascii_case(part)));
+		if let Some((_, modifier)) = modifier {
+			modifiers |= *modifier;
+		} else if let Some((_, code)) = HOTKEY_KEYS.iter().find(|(t, _)| t.eq_ignore_ascii_case(part)) {
+			key_code = Some(*code);
 		}
 	}
 
@@ -344,14 +2159,82 @@ fn parse_hotkey(hotkey: &str) -> Option<Shortcut> {
 	})
 }
 
-fn setup_global_shortcut(app: &AppHandle, hotkey: &str) -> Result<(), String> {
+/// Renders a shortcut the way the current platform labels keys, with
+/// modifier symbols on macOS and named modifiers elsewhere
+fn format_shortcut(shortcut: &Shortcut) -> String {
+	let key = HOTKEY_KEYS
+		.iter()
+		.find(|(_, code)| *code == shortcut.key)
+		.map_or_else(|| shortcut.key.to_string(), |(token, _)| token.to_string());
+
+	let modifiers: &[(Modifiers, &str)] = if cfg!(target_os = "macos") {
+		&[
+			(Modifiers::CONTROL, "⌃"),
+			(Modifiers::ALT, "⌥"),
+			(Modifiers::SHIFT, "⇧"),
+			(Modifiers::SUPER, "⌘"),
+		]
+	} else if cfg!(target_os = "windows") {
+		&[
+			(Modifiers::CONTROL, "Ctrl"),
+			(Modifiers::ALT, "Alt"),
+			(Modifiers::SHIFT, "Shift"),
+			(Modifiers::SUPER, "Win"),
+		]
+	} else {
+		&[
+			(Modifiers::CONTROL, "Ctrl"),
+			(Modifiers::ALT, "Alt"),
+			(Modifiers::SHIFT, "Shift"),
+			(Modifiers::SUPER, "Super"),
+		]
+	};
+
+	let mut parts: Vec<&str> = modifiers
+		.iter()
+		.filter(|(modifier, _)| shortcut.mods.contains(*modifier))
+		.map(|(_, label)| *label)
+		.collect();
+	parts.push(&key);
+
+	// macOS runs the symbols together, e.g. ⌃⇧R
+	let separator = if cfg!(target_os = "macos") { "" } else { "+" };
+	parts.join(separator)
+}
+
+/// Registers the recording hotkey and every entry of `action_hotkeys`.
+/// Only a bad recording hotkey is an error, broken extra ones are logged.
+/// Puts back the shortcuts of `config` after replacing them failed
+fn restore_global_shortcuts(app: &AppHandle, config: &Config) {
+	let _ = app.global_shortcut().unregister_all();
+	if let Err(e) = setup_global_shortcuts(app, config) {
+		eprintln!("Failed to restore global shortcuts: {}", e);
+	}
+}
+
+fn setup_global_shortcuts(app: &AppHandle, config: &Config) -> Result<(), String> {
+	let state = app.state::<Arc<AppStateManager>>();
+	if state.hotkey_enabled.load(Ordering::SeqCst) {
+		register_shortcut(app, &config.hotkey, HotkeyAction::ToggleRecording)?;
+	}
+
+	for (hotkey, action) in &config.action_hotkeys {
+		if let Err(e) = register_shortcut(app, hotkey, *action) {
+			eprintln!("Failed to register hotkey {}: {}", hotkey, e);
+		}
+	}
+
+	Ok(())
+}
+
+fn register_shortcut(app: &AppHandle, hotkey: &str, action: HotkeyAction) -> Result<(), String> {
 	let shortcut = parse_hotkey(hotkey).ok_or_else(|| format!("Invalid hotkey: {}", hotkey))?;
 
 	let app_clone = app.clone();
 	app.global_shortcut()
 		.on_shortcut(shortcut, move |_app, _shortcut, event| {
 			if event.state == ShortcutState::Pressed {
-				toggle_recording(&app_clone);
+				run_hotkey_action(&app_clone, action);
 			}
 		})
 		.map_err(|e| e.to_string())?;
@@ -359,8 +2242,145 @@ fn setup_global_shortcut(app: &AppHandle, hotkey: &str) -> Result<(), String> {
 	Ok(())
 }
 
+fn run_hotkey_action(app: &AppHandle, action: HotkeyAction) {
+	match action {
+		HotkeyAction::ToggleRecording => toggle_recording(app),
+		HotkeyAction::ToggleAutoCopy => toggle_auto_copy(app),
+		HotkeyAction::SwapLanguage => {
+			if let Err(e) = swap_language(app.clone(), app.state()) {
+				show_notification(app, NotificationKind::Error, "Error", &e);
+			}
+		}
+	}
+}
+
+/// Pauses or resumes the recording hotkey without touching the config,
+/// emitting "hotkey-enabled" with the new state. Other action hotkeys stay
+/// registered.
+#[tauri::command]
+fn set_hotkey_enabled(
+	app: AppHandle,
+	state: tauri::State<Arc<AppStateManager>>,
+	enabled: bool,
+) -> Result<(), String> {
+	let hotkey = state.get_config().hotkey;
+	if state.hotkey_enabled.load(Ordering::SeqCst) != enabled {
+		if enabled {
+			register_shortcut(&app, &hotkey, HotkeyAction::ToggleRecording)?;
+		} else if let Some(shortcut) = parse_hotkey(&hotkey) {
+			app.global_shortcut()
+				.unregister(shortcut)
+				.map_err(|e| e.to_string())?;
+		}
+		state.hotkey_enabled.store(enabled, Ordering::SeqCst);
+	}
+
+	if let Some(items) = app.try_state::<TrayMenuItems>() {
+		let _ = items.hotkey_active.set_checked(enabled);
+	}
+	let _ = app.emit("hotkey-enabled", enabled);
+
+	Ok(())
+}
+
+#[tauri::command]
+fn get_hotkey_enabled(state: tauri::State<Arc<AppStateManager>>) -> bool {
+	state.hotkey_enabled.load(Ordering::SeqCst)
+}
+
+/// Switches between the current and the previously used language,
+/// emitting "language-changed" with the new one
+#[tauri::command]
+fn swap_language(app: AppHandle, state: tauri::State<Arc<AppStateManager>>) -> Result<String, String> {
+	let mut config = state.get_config();
+	if !config.swap_language() {
+		return Err("No previous language to switch to".to_string());
+	}
+	let language = config.language.clone();
+	state.update_config(config).map_err(|e| e.to_string())?;
+	state.set_locked_language(None);
+
+	let _ = app.emit("language-changed", &language);
+	show_notification(&app, NotificationKind::Info, "Language", &format!("Switched to {}", language));
+	Ok(language)
+}
+
+fn toggle_auto_copy(app: &AppHandle) {
+	let state = app.state::<Arc<AppStateManager>>();
+	let mut config = state.get_config();
+	config.auto_copy = !config.auto_copy;
+	let enabled = config.auto_copy;
+
+	if let Err(e) = state.update_config(config) {
+		show_notification(app, NotificationKind::Error, "Error", &e.to_string());
+		return;
+	}
+
+	let _ = app.emit("auto-copy-changed", enabled);
+	show_notification(
+		app,
+		NotificationKind::Info,
+		"Auto-copy",
+		if enabled { "Auto-copy enabled" } else { "Auto-copy disabled" },
+	);
+}
+
+/// Transcribes a file with the configured model, without any of the GUI
+/// machinery
+fn transcribe_file_headless(path: &str) -> anyhow::Result<String> {
+	let config = Config::load(transcribe::is_supported_language)?;
+	let model_path = config
+		.model_path
+		.as_deref()
+		.ok_or_else(|| anyhow::anyhow!("No model configured"))?;
+
+	let transcriber = Transcriber::new(model_path, config.use_gpu, config.gpu_device)?.transcriber;
+	let options = TranscribeOptions::from_config(&config);
+
+	let text = transcriber.transcribe_file(Path::new(path), &options, &AtomicBool::new(false))?;
+	Ok(apply_corrections(text, &config))
+}
+
+/// Handles `--transcribe <file>`, returning the exit code when the app
+/// should exit instead of starting the GUI.
+fn run_cli(args: &[String]) -> Option<i32> {
+	let index = args.iter().position(|arg| arg == "--transcribe")?;
+
+	// Release builds on Windows have no console of their own, so output
+	// would be lost unless it goes to the terminal that started the app
+	#[cfg(target_os = "windows")]
+	// SAFETY: no arguments besides a constant, fails harmlessly when the
+	// parent has no console or output is already redirected
+	unsafe {
+		windows_sys::Win32::System::Console::AttachConsole(
+			windows_sys::Win32::System::Console::ATTACH_PARENT_PROCESS,
+		);
+	}
+
+	let Some(path) = args.get(index + 1) else {
+		eprintln!("Usage: shy-to-text --transcribe <file.wav>");
+		return Some(2);
+	};
+
+	match transcribe_file_headless(path) {
+		Ok(text) => {
+			println!("{}", text);
+			Some(0)
+		}
+		Err(e) => {
+			eprintln!("Transcription failed: {:#}", e);
+			Some(1)
+		}
+	}
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+	let args: Vec<String> = std::env::args().collect();
+	if let Some(code) = run_cli(&args) {
+		std::process::exit(code);
+	}
+
 	tauri::Builder::default()
 		.plugin(tauri_plugin_opener::init())
 		.plugin(tauri_plugin_global_shortcut::Builder::new().build())
@@ -368,15 +2388,68 @@ pub fn run() {
 		.plugin(tauri_plugin_clipboard_manager::init())
 		.plugin(tauri_plugin_dialog::init())
 		.plugin(tauri_plugin_fs::init())
+		.plugin(tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, None))
+		.on_window_event(|window, event| {
+			if window.label() != "main" {
+				return;
+			}
+			match event {
+				WindowEvent::CloseRequested { .. } => {
+					if let Some(window) = window.app_handle().get_webview_window("main")
+						&& let Err(e) = save_window_geometry(&window)
+					{
+						eprintln!("Failed to save window state: {}", e);
+					}
+				}
+				WindowEvent::Focused(false) => {
+					let state = window.app_handle().state::<Arc<AppStateManager>>();
+					*state.main_window_blurred.lock() = Some(Instant::now());
+				}
+				_ => {}
+			}
+		})
 		.setup(|app| {
 			let state_manager = AppStateManager::new();
 			let config = state_manager.get_config();
 
+			let fallback_model = state_manager.active_fallback_model();
+			let model_error = state_manager.get_error();
 			app.manage(state_manager);
 
+			sync_persistent_stream(&config);
+
+			// Keep the OS login item in line with the stored preference
+			let autostart_enabled = app.autolaunch().is_enabled().unwrap_or(false);
+			if autostart_enabled != config.autostart {
+				if let Err(e) = apply_autostart(app.handle(), config.autostart) {
+					eprintln!("{}", e);
+				}
+			}
+
 			let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
 			let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
-			let menu = Menu::with_items(app, &[&show, &quit])?;
+			let autostart = CheckMenuItem::with_id(
+				app,
+				"autostart",
+				"Start at Login",
+				true,
+				config.autostart,
+				None::<&str>,
+			)?;
+			let hotkey_active = CheckMenuItem::with_id(
+				app,
+				"hotkey_active",
+				"Hotkey Active",
+				true,
+				true,
+				None::<&str>,
+			)?;
+			let menu = Menu::with_items(app, &[&show, &hotkey_active, &autostart, &quit])?;
+
+			app.manage(TrayMenuItems {
+				autostart,
+				hotkey_active,
+			});
 
 			let icon = app.default_window_icon().cloned().unwrap_or_else(|| {
 				Image::new(&[], 1, 1)
@@ -389,12 +2462,34 @@ pub fn run() {
 				.tooltip("Shy to Text - Press F9 to record")
 				.on_menu_event(|app, event| match event.id.as_ref() {
 					"quit" => {
+						// Exiting skips the close request that normally saves it
+						if let Some(window) = app.get_webview_window("main")
+							&& let Err(e) = save_window_geometry(&window)
+						{
+							eprintln!("Failed to save window state: {}", e);
+						}
 						app.exit(0);
 					}
-					"show" => {
-						if let Some(window) = app.get_webview_window("main") {
-							let _ = window.show();
-							let _ = window.set_focus();
+					"show" => show_main_window(app),
+					"autostart" => {
+						let state = app.state::<Arc<AppStateManager>>();
+						let enabled = !state.get_config().autostart;
+						if let Err(e) = set_autostart(app.clone(), state, enabled) {
+							// Undo the check toggled by the click itself
+							if let Some(items) = app.try_state::<TrayMenuItems>() {
+								let _ = items.autostart.set_checked(!enabled);
+							}
+							show_notification(app, NotificationKind::Error, "Error", &e);
+						}
+					}
+					"hotkey_active" => {
+						let state = app.state::<Arc<AppStateManager>>();
+						let enabled = !state.hotkey_enabled.load(Ordering::SeqCst);
+						if let Err(e) = set_hotkey_enabled(app.clone(), state, enabled) {
+							if let Some(items) = app.try_state::<TrayMenuItems>() {
+								let _ = items.hotkey_active.set_checked(!enabled);
+							}
+							show_notification(app, NotificationKind::Error, "Error", &e);
 						}
 					}
 					_ => {}
@@ -407,35 +2502,109 @@ pub fn run() {
 					} = event
 					{
 						let app = tray.app_handle();
-						if let Some(window) = app.get_webview_window("main") {
-							let _ = window.show();
-							let _ = window.set_focus();
+						// Clicking again while it's in front puts it away
+						if main_window_in_front(app) {
+							hide_main_window(app);
+						} else {
+							show_main_window(app);
 						}
 					}
 				})
 				.build(app)?;
 
+			if let Some(window) = app.get_webview_window("main")
+				&& let Err(e) = restore_window_geometry(&window)
+			{
+				eprintln!("Failed to restore window state: {}", e);
+			}
+
+			if config.start_minimized {
+				if let Some(window) = app.get_webview_window("main") {
+					let _ = window.hide();
+				}
+			}
+
 			let app_handle = app.handle().clone();
-			if let Err(e) = setup_global_shortcut(&app_handle, &config.hotkey) {
+			if let Some(ref fallback) = fallback_model {
+				report_model_fallback(&app_handle, fallback);
+			}
+			if let Some(ref error) = model_error {
+				show_notification(&app_handle, NotificationKind::Error, "Model Not Found", error);
+				let _ = app_handle.emit("model-not-found", error);
+			}
+
+			if let Err(e) = setup_global_shortcuts(&app_handle, &config) {
 				eprintln!("Failed to setup global shortcut: {}", e);
 			}
 
+			start_power_monitor(app_handle.clone());
+			notify_mic_permission(&app_handle, permissions::check_mic_permission());
+
 			Ok(())
 		})
 		.invoke_handler(tauri::generate_handler![
 			get_app_state,
 			get_config,
+			get_effective_config,
+			get_loaded_model,
+			estimate_transcription_time,
+			format_hotkey,
+			get_bindable_keys,
+			get_diagnostics,
+			gpu_status,
+			compute_wer,
+			benchmark_threads,
+			save_window_state,
+			restore_window_state,
+			transcribe_samples,
+			set_vocabulary_file,
+			check_mic_permission,
+			swap_language,
+			set_hotkey_enabled,
+			get_hotkey_enabled,
+			get_hotkey_presets,
+			apply_hotkey_preset,
+			hide_window,
+			measure_noise_floor,
 			save_config,
+			patch_config,
+			clone_profile,
+			validate_hotkey,
 			get_available_models,
+			validate_all_models,
 			load_model,
+			restart_engine,
+			transcribe_file_range,
+			transcribe_clipboard_path,
+			is_model_loading,
 			has_model_loaded,
 			is_model_multilingual,
 			get_last_transcription,
+			get_last_waveform,
 			get_last_error,
+			clear_error,
+			get_locked_language,
+			clear_language_lock,
+			can_record,
 			get_models_directory,
 			get_input_devices,
+			get_input_device_details,
+			get_audio_host_info,
 			get_supported_languages,
+			recommend_model,
+			suggest_download,
+			quick_setup,
+			cancel_download,
+			resolve_review,
+			open_last_recording,
 			get_gpu_devices,
+			set_autostart,
+			get_autostart,
+			get_history,
+			export_history,
+			get_stats,
+			get_length_histogram,
+			reset_stats,
 		])
 		.run(tauri::generate_context!())
 		.expect("error while running tauri application");
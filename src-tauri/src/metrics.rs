@@ -0,0 +1,166 @@
+use serde::Serialize;
+
+/// How a word of the hypothesis lines up with the reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EditOp {
+	Match,
+	Substitution,
+	/// A reference word missing from the hypothesis
+	Deletion,
+	/// A hypothesis word not in the reference
+	Insertion,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AlignedWord {
+	pub op: EditOp,
+	pub reference: Option<String>,
+	pub hypothesis: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WerResult {
+	/// Errors divided by the number of reference words, can exceed 1.0
+	/// when the hypothesis has many insertions
+	pub wer: f32,
+	pub substitutions: usize,
+	pub deletions: usize,
+	pub insertions: usize,
+	pub reference_words: usize,
+	pub alignment: Vec<AlignedWord>,
+}
+
+/// Lowercased words with surrounding punctuation removed, so only the
+/// words themselves count as errors
+fn normalize_words(text: &str) -> Vec<String> {
+	text.split_whitespace()
+		.map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+		.filter(|word| !word.is_empty())
+		.collect()
+}
+
+/// Word error rate of `hypothesis` against `reference`, from the minimum
+/// number of word substitutions, deletions and insertions between them
+pub fn word_error_rate(hypothesis: &str, reference: &str) -> WerResult {
+	let hyp = normalize_words(hypothesis);
+	let reference = normalize_words(reference);
+	let (n, m) = (reference.len(), hyp.len());
+
+	// costs[i][j]: edits turning the first i reference words into the first j hypothesis words
+	let mut costs = vec![vec![0usize; m + 1]; n + 1];
+	for (i, row) in costs.iter_mut().enumerate() {
+		row[0] = i;
+	}
+	for (j, cost) in costs[0].iter_mut().enumerate() {
+		*cost = j;
+	}
+	for i in 1..=n {
+		for j in 1..=m {
+			let substitution = costs[i - 1][j - 1] + usize::from(reference[i - 1] != hyp[j - 1]);
+			costs[i][j] = substitution
+				.min(costs[i - 1][j] + 1)
+				.min(costs[i][j - 1] + 1);
+		}
+	}
+
+	// Walk back from the end to recover one optimal alignment
+	let mut alignment = Vec::new();
+	let (mut i, mut j) = (n, m);
+	while i > 0 || j > 0 {
+		let aligned = if i > 0
+			&& j > 0
+			&& costs[i][j] == costs[i - 1][j - 1] + usize::from(reference[i - 1] != hyp[j - 1])
+		{
+			i -= 1;
+			j -= 1;
+			let op = if reference[i] == hyp[j] {
+				EditOp::Match
+			} else {
+				EditOp::Substitution
+			};
+			AlignedWord {
+				op,
+				reference: Some(reference[i].clone()),
+				hypothesis: Some(hyp[j].clone()),
+			}
+		} else if i > 0 && costs[i][j] == costs[i - 1][j] + 1 {
+			i -= 1;
+			AlignedWord {
+				op: EditOp::Deletion,
+				reference: Some(reference[i].clone()),
+				hypothesis: None,
+			}
+		} else {
+			j -= 1;
+			AlignedWord {
+				op: EditOp::Insertion,
+				reference: None,
+				hypothesis: Some(hyp[j].clone()),
+			}
+		};
+		alignment.push(aligned);
+	}
+	alignment.reverse();
+
+	let count = |op| alignment.iter().filter(|word| word.op == op).count();
+	let (substitutions, deletions, insertions) = (
+		count(EditOp::Substitution),
+		count(EditOp::Deletion),
+		count(EditOp::Insertion),
+	);
+	let errors = substitutions + deletions + insertions;
+
+	WerResult {
+		wer: errors as f32 / n.max(1) as f32,
+		substitutions,
+		deletions,
+		insertions,
+		reference_words: n,
+		alignment,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_identical_text_has_zero_wer() {
+		let result = word_error_rate("Hello, world!", "hello world");
+		assert_eq!(result.wer, 0.0);
+		assert!(result.alignment.iter().all(|word| word.op == EditOp::Match));
+	}
+
+	#[test]
+	fn test_substitution_and_deletion() {
+		let result = word_error_rate("the cat sit on mat", "the cat sat on the mat");
+		assert_eq!(result.substitutions, 1);
+		assert_eq!(result.deletions, 1);
+		assert_eq!(result.insertions, 0);
+		assert!((result.wer - 2.0 / 6.0).abs() < 1e-6);
+
+		let deleted: Vec<_> = result
+			.alignment
+			.iter()
+			.filter(|word| word.op == EditOp::Deletion)
+			.collect();
+		assert_eq!(deleted[0].reference.as_deref(), Some("the"));
+	}
+
+	#[test]
+	fn test_insertions_can_exceed_one() {
+		let result = word_error_rate("yes yes yes", "yes");
+		assert_eq!(result.insertions, 2);
+		assert_eq!(result.wer, 2.0);
+	}
+
+	#[test]
+	fn test_empty_inputs() {
+		assert_eq!(word_error_rate("", "").wer, 0.0);
+
+		let result = word_error_rate("", "two words");
+		assert_eq!(result.deletions, 2);
+		assert_eq!(result.wer, 1.0);
+	}
+}
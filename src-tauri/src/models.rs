@@ -0,0 +1,350 @@
+use serde::Serialize;
+use std::path::Path;
+use sysinfo::{Disks, System};
+
+const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// Languages with enough training data that the small model transcribes them well
+const WELL_SUPPORTED_LANGUAGES: &[&str] = &[
+	"de", "es", "fr", "it", "ja", "ko", "nl", "pl", "pt", "ru", "sv", "tr", "uk", "zh",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecommendedModel {
+	/// File name of the model, e.g. `ggml-small-q8_0.bin`
+	pub name: String,
+	pub url: String,
+}
+
+impl RecommendedModel {
+	fn new(name: &str) -> Self {
+		Self {
+			name: name.to_string(),
+			url: model_url(name),
+		}
+	}
+}
+
+/// Models suited to a language, one favoring speed and one favoring accuracy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelRecommendation {
+	pub language: String,
+	pub speed: RecommendedModel,
+	pub quality: RecommendedModel,
+}
+
+pub fn model_url(name: &str) -> String {
+	format!("{}/{}", MODEL_BASE_URL, name)
+}
+
+/// Model downloaded by the quick setup, English-only when the system
+/// locale is English since it is faster and more accurate there.
+pub fn quick_setup_model_name(locale: Option<&str>) -> &'static str {
+	let is_english = locale
+		.map(|locale| locale.to_lowercase())
+		.is_some_and(|locale| locale == "en" || locale.starts_with("en-") || locale.starts_with("en_"));
+
+	if is_english {
+		"ggml-base.en.bin"
+	} else {
+		"ggml-base.bin"
+	}
+}
+
+/// Approximate disk and memory needs of a downloadable model
+struct ModelFootprint {
+	name: &'static str,
+	/// English-only variant, preferred when transcribing English
+	english_name: Option<&'static str>,
+	disk_mb: u64,
+	memory_mb: u64,
+	/// Too slow on the CPU to be comfortable for dictation
+	needs_gpu: bool,
+}
+
+/// Models considered by `suggest_download`, from most to least accurate
+const MODEL_FOOTPRINTS: &[ModelFootprint] = &[
+	ModelFootprint {
+		name: "ggml-large-v3.bin",
+		english_name: None,
+		disk_mb: 3100,
+		memory_mb: 3900,
+		needs_gpu: true,
+	},
+	ModelFootprint {
+		name: "ggml-large-v3-turbo-q8_0.bin",
+		english_name: None,
+		disk_mb: 874,
+		memory_mb: 1600,
+		needs_gpu: true,
+	},
+	ModelFootprint {
+		name: "ggml-medium-q8_0.bin",
+		english_name: Some("ggml-medium.en-q8_0.bin"),
+		disk_mb: 823,
+		memory_mb: 1500,
+		needs_gpu: false,
+	},
+	ModelFootprint {
+		name: "ggml-small-q8_0.bin",
+		english_name: Some("ggml-small.en-q8_0.bin"),
+		disk_mb: 264,
+		memory_mb: 600,
+		needs_gpu: false,
+	},
+	ModelFootprint {
+		name: "ggml-base-q8_0.bin",
+		english_name: Some("ggml-base.en-q8_0.bin"),
+		disk_mb: 82,
+		memory_mb: 250,
+		needs_gpu: false,
+	},
+	ModelFootprint {
+		name: "ggml-tiny-q8_0.bin",
+		english_name: Some("ggml-tiny.en-q8_0.bin"),
+		disk_mb: 44,
+		memory_mb: 150,
+		needs_gpu: false,
+	},
+];
+
+/// Free space left on the disk after a download for it to be comfortable
+const DISK_HEADROOM_MB: u64 = 500;
+
+/// What the machine has to run a model with, `None` where it couldn't be
+/// detected and shouldn't limit the choice
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MachineResources {
+	pub free_disk_mb: Option<u64>,
+	pub memory_mb: Option<u64>,
+	/// A dedicated GPU that whisper can use
+	pub gpu_name: Option<String>,
+	pub gpu_memory_mb: Option<u64>,
+}
+
+impl MachineResources {
+	/// Free space on the disk holding `models_dir` and total memory, without
+	/// the GPU and its memory which the caller fills in
+	pub fn detect(models_dir: &Path) -> Self {
+		let mut system = System::new();
+		system.refresh_memory();
+		let memory_mb = Some(system.total_memory() / 1_000_000).filter(|&mb| mb > 0);
+
+		// The disk mounted deepest along the path is the one holding it
+		let disks = Disks::new_with_refreshed_list();
+		let free_disk_mb = disks
+			.list()
+			.iter()
+			.filter(|disk| models_dir.starts_with(disk.mount_point()))
+			.max_by_key(|disk| disk.mount_point().as_os_str().len())
+			.map(|disk| disk.available_space() / 1_000_000);
+
+		Self {
+			free_disk_mb,
+			memory_mb,
+			gpu_name: None,
+			gpu_memory_mb: None,
+		}
+	}
+}
+
+/// The best model this machine can comfortably run and has room for
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadSuggestion {
+	/// `None` when not even the smallest model fits
+	pub model: Option<RecommendedModel>,
+	pub explanation: String,
+	pub resources: MachineResources,
+}
+
+/// Why `footprint` doesn't suit `resources`, if it doesn't
+fn footprint_problem(footprint: &ModelFootprint, resources: &MachineResources) -> Option<String> {
+	if footprint.needs_gpu && resources.gpu_name.is_none() {
+		return Some("needs a dedicated GPU to run comfortably".to_string());
+	}
+	if footprint.needs_gpu
+		&& let Some(gpu_memory_mb) = resources.gpu_memory_mb
+		&& footprint.memory_mb > gpu_memory_mb
+	{
+		return Some(format!(
+			"needs ~{:.1}GB of GPU memory and the GPU has {:.1}GB",
+			footprint.memory_mb as f64 / 1000.0,
+			gpu_memory_mb as f64 / 1000.0
+		));
+	}
+	// Half the memory, leaving the rest for the system and other programs
+	if let Some(memory_mb) = resources.memory_mb
+		&& footprint.memory_mb > memory_mb / 2
+	{
+		return Some(format!(
+			"needs ~{:.1}GB of memory",
+			footprint.memory_mb as f64 / 1000.0
+		));
+	}
+	if let Some(free_mb) = resources.free_disk_mb
+		&& footprint.disk_mb + DISK_HEADROOM_MB > free_mb
+	{
+		return Some(format!(
+			"needs ~{}MB of disk space and {}MB are free",
+			footprint.disk_mb, free_mb
+		));
+	}
+	None
+}
+
+/// Picks the most accurate model in `MODEL_FOOTPRINTS` that fits
+/// `resources`, explaining why better ones were passed over
+pub fn suggest_download(resources: MachineResources, language: &str) -> DownloadSuggestion {
+	let english = language.trim().eq_ignore_ascii_case("en");
+	let name_of = |footprint: &ModelFootprint| match footprint.english_name {
+		Some(name) if english => name,
+		_ => footprint.name,
+	};
+
+	let mut first_problem: Option<(&str, String)> = None;
+	for footprint in MODEL_FOOTPRINTS {
+		let name = name_of(footprint);
+		match footprint_problem(footprint, &resources) {
+			Some(problem) => {
+				first_problem.get_or_insert((name, problem));
+			}
+			None => {
+				let explanation = match first_problem {
+					Some((best, problem)) => format!("{} {}; suggesting {} instead", best, problem, name),
+					None => format!("{} fits this machine comfortably", name),
+				};
+				return DownloadSuggestion {
+					model: Some(RecommendedModel::new(name)),
+					explanation,
+					resources,
+				};
+			}
+		}
+	}
+
+	let smallest = &MODEL_FOOTPRINTS[MODEL_FOOTPRINTS.len() - 1];
+	let explanation = match footprint_problem(smallest, &resources) {
+		Some(problem) => format!("No model fits, even {} {}", name_of(smallest), problem),
+		None => "No model fits this machine".to_string(),
+	};
+	DownloadSuggestion {
+		model: None,
+		explanation,
+		resources,
+	}
+}
+
+pub fn recommend_model(language: &str) -> ModelRecommendation {
+	let language = language.trim().to_lowercase();
+
+	let (speed, quality) = match language.as_str() {
+		// English-only models are faster and more accurate for English
+		"en" => ("ggml-base.en-q8_0.bin", "ggml-small.en-q8_0.bin"),
+		"auto" => ("ggml-small-q8_0.bin", "ggml-large-v3-turbo-q8_0.bin"),
+		code if WELL_SUPPORTED_LANGUAGES.contains(&code) => {
+			("ggml-small-q8_0.bin", "ggml-large-v3-turbo-q8_0.bin")
+		}
+		_ => ("ggml-medium-q8_0.bin", "ggml-large-v3-turbo-q8_0.bin"),
+	};
+
+	ModelRecommendation {
+		language,
+		speed: RecommendedModel::new(speed),
+		quality: RecommendedModel::new(quality),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn resources(free_disk_mb: u64, memory_mb: u64, gpu: bool) -> MachineResources {
+		MachineResources {
+			free_disk_mb: Some(free_disk_mb),
+			memory_mb: Some(memory_mb),
+			gpu_name: gpu.then(|| "Test GPU".to_string()),
+			gpu_memory_mb: None,
+		}
+	}
+
+	#[test]
+	fn test_suggests_largest_model_with_gpu_and_room() {
+		let suggestion = suggest_download(resources(100_000, 16_000, true), "auto");
+		assert_eq!(suggestion.model.unwrap().name, "ggml-large-v3.bin");
+		assert!(suggestion.explanation.contains("comfortably"));
+	}
+
+	#[test]
+	fn test_explains_smaller_suggestion() {
+		let suggestion = suggest_download(resources(100_000, 16_000, false), "auto");
+		assert_eq!(suggestion.model.unwrap().name, "ggml-medium-q8_0.bin");
+		assert_eq!(
+			suggestion.explanation,
+			"ggml-large-v3.bin needs a dedicated GPU to run comfortably; suggesting ggml-medium-q8_0.bin instead"
+		);
+
+		// Low memory and a GPU: large-v3 doesn't fit in half of 4GB
+		let suggestion = suggest_download(resources(100_000, 4000, true), "en");
+		assert_eq!(suggestion.model.unwrap().name, "ggml-large-v3-turbo-q8_0.bin");
+		assert!(suggestion.explanation.contains("~3.9GB of memory"));
+	}
+
+	#[test]
+	fn test_gpu_memory_limits_gpu_models() {
+		let mut resources = resources(100_000, 16_000, true);
+		resources.gpu_memory_mb = Some(2000);
+		let suggestion = suggest_download(resources, "auto");
+		assert_eq!(suggestion.model.unwrap().name, "ggml-large-v3-turbo-q8_0.bin");
+		assert_eq!(
+			suggestion.explanation,
+			"ggml-large-v3.bin needs ~3.9GB of GPU memory and the GPU has 2.0GB; suggesting ggml-large-v3-turbo-q8_0.bin instead"
+		);
+	}
+
+	#[test]
+	fn test_english_and_disk_space() {
+		let suggestion = suggest_download(resources(900, 16_000, false), "en");
+		assert_eq!(suggestion.model.unwrap().name, "ggml-small.en-q8_0.bin");
+
+		let suggestion = suggest_download(resources(100, 16_000, false), "auto");
+		assert!(suggestion.model.is_none());
+		assert!(suggestion.explanation.starts_with("No model fits, even ggml-tiny-q8_0.bin"));
+	}
+
+	#[test]
+	fn test_undetected_resources_do_not_limit() {
+		let suggestion = suggest_download(MachineResources::default(), "auto");
+		assert_eq!(suggestion.model.unwrap().name, "ggml-medium-q8_0.bin");
+	}
+
+	#[test]
+	fn test_english_recommends_english_only_models() {
+		let recommendation = recommend_model("EN");
+		assert_eq!(recommendation.language, "en");
+		assert!(recommendation.speed.name.contains(".en"));
+		assert!(recommendation.quality.name.contains(".en"));
+	}
+
+	#[test]
+	fn test_less_common_language_gets_larger_speed_model() {
+		assert_eq!(recommend_model("it").speed.name, "ggml-small-q8_0.bin");
+		assert_eq!(recommend_model("sw").speed.name, "ggml-medium-q8_0.bin");
+	}
+
+	#[test]
+	fn test_quick_setup_model_follows_locale() {
+		assert_eq!(quick_setup_model_name(Some("en-US")), "ggml-base.en.bin");
+		assert_eq!(quick_setup_model_name(Some("en_GB.UTF-8")), "ggml-base.en.bin");
+		assert_eq!(quick_setup_model_name(Some("it-IT")), "ggml-base.bin");
+		assert_eq!(quick_setup_model_name(None), "ggml-base.bin");
+	}
+
+	#[test]
+	fn test_recommendation_url_points_to_model() {
+		let recommendation = recommend_model("fr");
+		assert_eq!(
+			recommendation.quality.url,
+			format!("{}/ggml-large-v3-turbo-q8_0.bin", MODEL_BASE_URL)
+		);
+	}
+}
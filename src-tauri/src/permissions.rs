@@ -0,0 +1,36 @@
+use serde::Serialize;
+
+/// Whether the app may record from the microphone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MicPermission {
+	Granted,
+	Denied,
+	/// Not asked yet, the OS prompts on the first recording
+	Undetermined,
+}
+
+/// Reads the microphone authorization from AVFoundation. Without it
+/// macOS hands the app silent audio instead of failing.
+#[cfg(target_os = "macos")]
+pub fn check_mic_permission() -> MicPermission {
+	use objc2_av_foundation::{AVAuthorizationStatus, AVCaptureDevice, AVMediaTypeAudio};
+
+	// SAFETY: AVMediaTypeAudio is a constant NSString provided by AVFoundation
+	let Some(media_type) = (unsafe { AVMediaTypeAudio }) else {
+		return MicPermission::Granted;
+	};
+	match unsafe { AVCaptureDevice::authorizationStatusForMediaType(media_type) } {
+		AVAuthorizationStatus::Authorized => MicPermission::Granted,
+		AVAuthorizationStatus::NotDetermined => MicPermission::Undetermined,
+		// Restricted covers parental controls and device management
+		_ => MicPermission::Denied,
+	}
+}
+
+/// Other platforms don't gate microphone access per app in a way that
+/// can be queried, so access is assumed
+#[cfg(not(target_os = "macos"))]
+pub fn check_mic_permission() -> MicPermission {
+	MicPermission::Granted
+}
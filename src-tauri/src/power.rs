@@ -0,0 +1,14 @@
+use anyhow::{Context, Result};
+use starship_battery::{Manager, State};
+
+/// Whether the machine is running off its battery. Desktops without a
+/// battery count as being on AC.
+pub fn on_battery() -> Result<bool> {
+	let manager = Manager::new().context("Failed to query power source")?;
+	for battery in manager.batteries()? {
+		if battery?.state() == State::Discharging {
+			return Ok(true);
+		}
+	}
+	Ok(false)
+}
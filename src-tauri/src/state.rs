@@ -1,8 +1,20 @@
-use crate::config::Config;
-use crate::transcribe::Transcriber;
-use parking_lot::Mutex;
+use crate::audio;
+use crate::config::{Config, ModelMetadata};
+use crate::focus;
+use crate::gpu;
+use crate::history::{ExportFormat, History, HistoryEntry};
+use crate::power;
+use crate::stats::Stats;
+use crate::transcribe::{self, Transcriber, TranscriberLoadResult};
+use parking_lot::{Mutex, MutexGuard};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -10,6 +22,46 @@ pub enum AppState {
 	Idle,
 	Recording,
 	Transcribing,
+	/// Measuring the noise floor, recordings can't start meanwhile
+	Calibrating,
+}
+
+/// The stored config together with the values actually in effect at runtime
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+	pub config: Config,
+	pub n_threads: i32,
+	pub device: String,
+	pub gpu_active: bool,
+	pub forced_english: bool,
+	pub loaded_model_path: Option<String>,
+	pub model_multilingual: Option<bool>,
+}
+
+/// The model held by the transcriber, which may differ from the configured one
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedModel {
+	pub path: String,
+	pub name: String,
+	pub multilingual: bool,
+}
+
+/// Whether the loaded model actually runs on the GPU
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuStatus {
+	/// GPU use was asked for, including when loading then fell back
+	pub requested: bool,
+	pub active: bool,
+	pub device_name: Option<String>,
+	/// The last load asked for the GPU but ended up on the CPU
+	pub fell_back: bool,
+}
+
+/// Whether a recording can start right now, with the reasons it can't
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordReadiness {
+	pub ready: bool,
+	pub reasons: Vec<String>,
 }
 
 pub struct AppStateManager {
@@ -17,7 +69,210 @@ pub struct AppStateManager {
 	pub config: Mutex<Config>,
 	pub transcriber: Mutex<Option<Transcriber>>,
 	pub last_transcription: Mutex<String>,
+	pub last_recording: Mutex<Vec<f32>>,
+	/// Where the last recording was saved when `save_recordings` is on
+	pub last_recording_path: Mutex<Option<PathBuf>>,
 	pub error: Mutex<Option<String>>,
+	pub cancel_transcription: AtomicBool,
+	pub stats: Mutex<Stats>,
+	pub history: Mutex<History>,
+	pub last_completed: Mutex<Option<Instant>>,
+	pub model_loading: AtomicBool,
+	pub load_lock: Mutex<()>,
+	/// First auto-detected language, forced while `lock_detected_language` is on
+	pub locked_language: Mutex<Option<String>>,
+	pub realtime_factors: Mutex<RealtimeFactors>,
+	/// Set to stop the model download in progress
+	pub cancel_download: AtomicBool,
+	/// Receives the user's answer for the transcription awaiting review
+	pub pending_review: Mutex<Option<mpsc::Sender<bool>>>,
+	/// Set while continuous mode keeps recording segment after segment
+	pub dictation_session: AtomicBool,
+	/// Cleared while the recording hotkey is paused, until the app restarts
+	pub hotkey_enabled: AtomicBool,
+	/// Window that had focus before the main window was shown
+	pub previous_focus: Mutex<Option<focus::WindowHandle>>,
+	/// When the main window last lost focus
+	pub main_window_blurred: Mutex<Option<Instant>>,
+	/// Cleared once a system notification fails, after which notifications
+	/// are shown inside the window
+	pub notifications_working: AtomicBool,
+	/// Set when the last model load fell back from GPU to CPU
+	pub gpu_fell_back: AtomicBool,
+}
+
+/// How many recent runs the realtime factor is averaged over
+const REALTIME_FACTOR_WINDOW: usize = 5;
+
+/// Recordings shorter than this are dominated by fixed overhead and would
+/// skew the realtime factor
+const MIN_TIMED_AUDIO_SECS: f64 = 1.0;
+
+/// Model path and GPU device a realtime factor was measured with
+type RealtimeFactorKey = (String, Option<i32>);
+
+/// Moving averages of seconds spent transcribing per second of audio
+#[derive(Debug, Default)]
+pub struct RealtimeFactors {
+	recent: HashMap<RealtimeFactorKey, VecDeque<f32>>,
+}
+
+impl RealtimeFactors {
+	pub fn record(&mut self, key: RealtimeFactorKey, factor: f32) {
+		let recent = self.recent.entry(key).or_default();
+		if recent.len() == REALTIME_FACTOR_WINDOW {
+			recent.pop_front();
+		}
+		recent.push_back(factor);
+	}
+
+	pub fn average(&self, key: &RealtimeFactorKey) -> Option<f32> {
+		let recent = self.recent.get(key).filter(|recent| !recent.is_empty())?;
+		Some(recent.iter().sum::<f32>() / recent.len() as f32)
+	}
+}
+
+/// Pessimistic CPU realtime factor for a model size, used until a run has
+/// been timed
+fn default_realtime_factor(model_type: &str) -> f32 {
+	match model_type {
+		"tiny" => 0.1,
+		"base" => 0.2,
+		"small" => 0.5,
+		"medium" => 1.2,
+		"large" => 2.5,
+		_ => 1.0,
+	}
+}
+
+/// What actually happened when loading a model
+#[derive(Debug, Clone, Default)]
+pub struct ModelLoadOutcome {
+	/// GPU loading failed and the model runs on CPU
+	pub gpu_fallback: bool,
+	/// Path of the fallback model loaded instead of the requested one
+	pub fallback_model: Option<String>,
+}
+
+fn gpu_device_name(id: i32) -> String {
+	gpu::get_gpu_devices()
+		.into_iter()
+		.find(|d| d.id == id)
+		.map(|d| d.name)
+		.unwrap_or_else(|| format!("GPU {}", id))
+}
+
+/// Tries `model_path` and then each of the configured fallback models,
+/// returning the first one that loads along with its path.
+fn load_first_available(
+	model_path: &str,
+	config: &Config,
+) -> anyhow::Result<(TranscriberLoadResult, String)> {
+	let candidates =
+		std::iter::once(model_path).chain(config.fallback_models.iter().map(String::as_str));
+	let mut first_error = None;
+
+	let backend_supported = gpu::is_backend_supported(&config.gpu_backend);
+	if config.use_gpu && !backend_supported {
+		eprintln!(
+			"GPU backend \"{}\" is not available in this build, using CPU",
+			config.gpu_backend
+		);
+	}
+	let use_gpu = config.use_gpu && backend_supported;
+
+	for path in candidates {
+		match Transcriber::new(path, use_gpu, config.gpu_device) {
+			Ok(result) => return Ok((result, path.to_string())),
+			Err(e) => {
+				eprintln!("Failed to load model {}: {}", path, e);
+				first_error.get_or_insert(e);
+			}
+		}
+	}
+
+	Err(first_error.unwrap_or_else(|| anyhow::anyhow!("No model to load")))
+}
+
+/// Serializes model loads and keeps `model_loading` set while held
+pub struct LoadingGuard<'a> {
+	state: &'a AppStateManager,
+	_lock: MutexGuard<'a, ()>,
+}
+
+impl Drop for LoadingGuard<'_> {
+	fn drop(&mut self) {
+		self.state.model_loading.store(false, Ordering::SeqCst);
+	}
+}
+
+fn compare_and_set(state: &Mutex<AppState>, expected: AppState, new: AppState) -> bool {
+	let mut current = state.lock();
+	if *current != expected {
+		return false;
+	}
+	*current = new;
+	true
+}
+
+/// Moves from Idle to Recording and stores the session `start` opens in
+/// `slot`. The slot stays locked until then, so a concurrent stop that
+/// sees Recording always finds the session. Returns `None` without
+/// calling `start` when not idle, and goes back to Idle if it fails.
+fn begin_session<S, E>(
+	state: &Mutex<AppState>,
+	slot: &Mutex<Option<S>>,
+	start: impl FnOnce() -> Result<S, E>,
+) -> Option<Result<(), E>> {
+	let mut slot = slot.lock();
+	if !compare_and_set(state, AppState::Idle, AppState::Recording) {
+		return None;
+	}
+
+	match start() {
+		Ok(session) => {
+			*slot = Some(session);
+			Some(Ok(()))
+		}
+		Err(e) => {
+			*state.lock() = AppState::Idle;
+			Some(Err(e))
+		}
+	}
+}
+
+/// Moves from Recording to Transcribing and takes the session out of
+/// `slot`. Returns `None` when not recording, so only one of several
+/// concurrent stops gets the session.
+fn end_session<S>(state: &Mutex<AppState>, slot: &Mutex<Option<S>>) -> Option<Option<S>> {
+	if !compare_and_set(state, AppState::Recording, AppState::Transcribing) {
+		return None;
+	}
+	Some(slot.lock().take())
+}
+
+/// How often a watchdog passes on `cancel_transcription` to its own run
+const WATCHDOG_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Cancels the running transcription if it isn't finished in time
+pub struct TranscriptionWatchdog {
+	cancel: Arc<AtomicBool>,
+	done: mpsc::Sender<()>,
+	handle: JoinHandle<bool>,
+}
+
+impl TranscriptionWatchdog {
+	/// The flag to transcribe with, set only for this run
+	pub fn cancel(&self) -> &AtomicBool {
+		&self.cancel
+	}
+
+	/// Stops the watchdog, returning whether it had to cancel the transcription
+	/// because it timed out
+	pub fn finish(self) -> bool {
+		drop(self.done);
+		self.handle.join().unwrap_or(false)
+	}
 }
 
 unsafe impl Send for AppStateManager {}
@@ -25,11 +280,38 @@ unsafe impl Sync for AppStateManager {}
 
 impl AppStateManager {
 	pub fn new() -> Arc<Self> {
-		let mut config = Config::load().unwrap_or_default();
+		let mut config = Config::load(transcribe::is_supported_language).unwrap_or_default();
+		let mut error = None;
 
-		let transcriber = if let Some(ref model_path) = config.model_path {
-			match Transcriber::new(model_path, config.use_gpu, config.gpu_device) {
-				Ok(result) => {
+		// Follow the model if it was moved since the config was saved
+		let model_path = config.model_path.as_ref().and_then(|configured| {
+			let resolved = config.resolve_model_path();
+			match resolved {
+				Some(ref path) if path != configured => {
+					eprintln!("Configured model moved to {}", path);
+				}
+				Some(_) => {}
+				None => {
+					let name = config.model_name.as_deref().unwrap_or(configured);
+					error = Some(format!("Configured model not found: {}", name));
+				}
+			}
+			resolved
+		});
+		// Also records the name for configs saved before it was stored
+		if let Some(ref path) = model_path
+			&& (model_path != config.model_path || config.model_name.is_none())
+		{
+			config.model_path = Some(path.clone());
+			config.model_name = Config::model_name_for(path);
+			let _ = config.save();
+		}
+
+		let mut gpu_fell_back = false;
+		let transcriber = if let Some(ref model_path) = model_path {
+			match load_first_available(model_path, &config) {
+				Ok((result, _)) => {
+					gpu_fell_back = result.gpu_fallback;
 					if result.gpu_fallback {
 						config.use_gpu = false;
 						let _ = config.save();
@@ -47,7 +329,25 @@ impl AppStateManager {
 			config: Mutex::new(config),
 			transcriber: Mutex::new(transcriber),
 			last_transcription: Mutex::new(String::new()),
-			error: Mutex::new(None),
+			last_recording: Mutex::new(Vec::new()),
+			last_recording_path: Mutex::new(None),
+			error: Mutex::new(error),
+			cancel_transcription: AtomicBool::new(false),
+			stats: Mutex::new(Stats::load().unwrap_or_default()),
+			history: Mutex::new(History::load().unwrap_or_default()),
+			last_completed: Mutex::new(None),
+			model_loading: AtomicBool::new(false),
+			load_lock: Mutex::new(()),
+			locked_language: Mutex::new(None),
+			realtime_factors: Mutex::new(RealtimeFactors::default()),
+			cancel_download: AtomicBool::new(false),
+			pending_review: Mutex::new(None),
+			previous_focus: Mutex::new(None),
+			main_window_blurred: Mutex::new(None),
+			dictation_session: AtomicBool::new(false),
+			hotkey_enabled: AtomicBool::new(true),
+			notifications_working: AtomicBool::new(true),
+			gpu_fell_back: AtomicBool::new(gpu_fell_back),
 		})
 	}
 
@@ -59,51 +359,104 @@ impl AppStateManager {
 		*self.state.lock() = state;
 	}
 
+	/// Moves to `new` only if the state is still `expected`, so that two
+	/// triggers racing on the same transition can't both act on it.
+	pub fn try_set_state(&self, expected: AppState, new: AppState) -> bool {
+		compare_and_set(&self.state, expected, new)
+	}
+
+	/// Starts a recording session into `slot`, see `begin_session`
+	pub fn begin_session<S, E>(
+		&self,
+		slot: &Mutex<Option<S>>,
+		start: impl FnOnce() -> Result<S, E>,
+	) -> Option<Result<(), E>> {
+		begin_session(&self.state, slot, start)
+	}
+
+	/// Takes the recording session out of `slot`, see `end_session`
+	pub fn end_session<S>(&self, slot: &Mutex<Option<S>>) -> Option<Option<S>> {
+		end_session(&self.state, slot)
+	}
+
 	pub fn get_config(&self) -> Config {
 		self.config.lock().clone()
 	}
 
 	pub fn update_config(&self, config: Config) -> anyhow::Result<()> {
+		config.validate()?;
 		config.save()?;
 		*self.config.lock() = config;
 		Ok(())
 	}
 
-	/// Loads a model with the current GPU configuration.
-	/// Returns true if GPU fallback to CPU occurred.
-	pub fn load_model(&self, model_path: &str) -> anyhow::Result<bool> {
-		let (use_gpu, gpu_device) = {
-			let config = self.config.lock();
-			(config.use_gpu, config.gpu_device)
-		};
+	/// Waits for any running model load to finish and marks a new one as
+	/// in progress until the returned guard is dropped.
+	pub fn begin_loading(&self) -> LoadingGuard<'_> {
+		let lock = self.load_lock.lock();
+		self.model_loading.store(true, Ordering::SeqCst);
+		LoadingGuard { state: self, _lock: lock }
+	}
+
+	pub fn is_model_loading(&self) -> bool {
+		self.model_loading.load(Ordering::SeqCst)
+	}
 
-		let result = Transcriber::new(model_path, use_gpu, gpu_device)?;
+	/// Loads a model with the current GPU configuration, trying the
+	/// configured fallback models if it fails.
+	pub fn load_model(&self, model_path: &str) -> anyhow::Result<ModelLoadOutcome> {
+		let _loading = self.begin_loading();
+		let config = self.get_config();
+
+		let (result, loaded_path) = load_first_available(model_path, &config)?;
 		*self.transcriber.lock() = Some(result.transcriber);
+		self.gpu_fell_back.store(result.gpu_fallback, Ordering::SeqCst);
 
 		let mut config = self.config.lock();
 		config.model_path = Some(model_path.to_string());
+		config.model_name = Config::model_name_for(model_path);
 		if result.gpu_fallback {
 			config.use_gpu = false;
 		}
 		config.save()?;
 
-		Ok(result.gpu_fallback)
+		Ok(ModelLoadOutcome {
+			gpu_fallback: result.gpu_fallback,
+			fallback_model: (loaded_path != model_path).then_some(loaded_path),
+		})
 	}
 
-	/// Reloads the currently loaded model with updated GPU configuration.
-	/// Returns true if GPU fallback to CPU occurred.
-	pub fn reload_model(&self) -> anyhow::Result<bool> {
-		let (model_path, use_gpu, gpu_device) = {
-			let config = self.config.lock();
-			let model_path = config
-				.model_path
-				.clone()
-				.ok_or_else(|| anyhow::anyhow!("No model loaded"))?;
-			(model_path, config.use_gpu, config.gpu_device)
-		};
+	/// Loads a model without making it the configured one, for switches
+	/// that should be undone later such as running on battery.
+	pub fn load_model_temporarily(&self, model_path: &str) -> anyhow::Result<ModelLoadOutcome> {
+		let _loading = self.begin_loading();
+		let config = self.get_config();
 
-		let result = Transcriber::new(&model_path, use_gpu, gpu_device)?;
+		let (result, loaded_path) = load_first_available(model_path, &config)?;
 		*self.transcriber.lock() = Some(result.transcriber);
+		self.gpu_fell_back.store(result.gpu_fallback, Ordering::SeqCst);
+
+		Ok(ModelLoadOutcome {
+			gpu_fallback: result.gpu_fallback,
+			fallback_model: (loaded_path != model_path).then_some(loaded_path),
+		})
+	}
+
+	/// Reloads the model with updated GPU configuration, keeping the
+	/// low-power model while on battery.
+	pub fn reload_model(&self) -> anyhow::Result<ModelLoadOutcome> {
+		let _loading = self.begin_loading();
+		let config = self.get_config();
+		let on_battery = config.adaptive_power && power::on_battery().unwrap_or(false);
+		let model_path = config
+			.model_for_power(on_battery)
+			.or(config.model_path.as_deref())
+			.map(str::to_string)
+			.ok_or_else(|| anyhow::anyhow!("No model loaded"))?;
+
+		let (result, loaded_path) = load_first_available(&model_path, &config)?;
+		*self.transcriber.lock() = Some(result.transcriber);
+		self.gpu_fell_back.store(result.gpu_fallback, Ordering::SeqCst);
 
 		if result.gpu_fallback {
 			let mut config = self.config.lock();
@@ -111,7 +464,60 @@ impl AppStateManager {
 			config.save()?;
 		}
 
-		Ok(result.gpu_fallback)
+		Ok(ModelLoadOutcome {
+			gpu_fallback: result.gpu_fallback,
+			fallback_model: (loaded_path != model_path).then_some(loaded_path),
+		})
+	}
+
+	/// Cancels any in-flight transcription, drops the current transcriber and
+	/// loads the configured model again from scratch.
+	pub fn restart_engine(&self) -> anyhow::Result<ModelLoadOutcome> {
+		match self.get_state() {
+			AppState::Recording => anyhow::bail!("Cannot restart the engine while recording"),
+			AppState::Calibrating => {
+				anyhow::bail!("Cannot restart the engine while measuring the noise floor")
+			}
+			AppState::Idle | AppState::Transcribing => {}
+		}
+
+		self.cancel_transcription.store(true, Ordering::SeqCst);
+		{
+			// Blocks until a running transcription has been aborted
+			let mut transcriber = self.transcriber.lock();
+			self.cancel_transcription.store(false, Ordering::SeqCst);
+			*transcriber = None;
+		}
+
+		if self.config.lock().model_path.is_none() {
+			return Ok(ModelLoadOutcome::default());
+		}
+
+		self.reload_model()
+	}
+
+	/// Path of the loaded model when it is a fallback rather than the configured one
+	pub fn active_fallback_model(&self) -> Option<String> {
+		let configured = self.config.lock().model_path.clone()?;
+		let transcriber = self.transcriber.lock();
+		let loaded = transcriber.as_ref()?.model_path();
+		(loaded != configured).then(|| loaded.to_string())
+	}
+
+	pub fn loaded_model(&self) -> Option<LoadedModel> {
+		let transcriber = self.transcriber.lock();
+		let transcriber = transcriber.as_ref()?;
+		let path = transcriber.model_path().to_string();
+		let name = Path::new(&path)
+			.file_name()
+			.map(|name| name.to_string_lossy().into_owned())
+			.unwrap_or_else(|| path.clone());
+
+		Some(LoadedModel {
+			path,
+			name,
+			multilingual: transcriber.is_multilingual(),
+		})
 	}
 
 	pub fn has_model(&self) -> bool {
@@ -126,6 +532,79 @@ impl AppStateManager {
 			.unwrap_or(false)
 	}
 
+	pub fn effective_config(&self) -> EffectiveConfig {
+		let config = self.get_config();
+
+		let (loaded_model_path, model_multilingual, gpu_device) = {
+			let transcriber = self.transcriber.lock();
+			match transcriber.as_ref() {
+				Some(t) => (
+					Some(t.model_path().to_string()),
+					Some(t.is_multilingual()),
+					t.gpu_device(),
+				),
+				None => (None, None, None),
+			}
+		};
+
+		let device = gpu_device.map_or_else(|| "CPU".to_string(), gpu_device_name);
+
+		let n_threads = loaded_model_path
+			.as_deref()
+			.and_then(|path| config.threads_for_model(path))
+			.unwrap_or_else(transcribe::num_cpus);
+
+		EffectiveConfig {
+			config,
+			n_threads,
+			device,
+			gpu_active: gpu_device.is_some(),
+			forced_english: model_multilingual == Some(false),
+			loaded_model_path,
+			model_multilingual,
+		}
+	}
+
+	pub fn gpu_status(&self) -> GpuStatus {
+		let fell_back = self.gpu_fell_back.load(Ordering::SeqCst);
+		let gpu_device = self.transcriber.lock().as_ref().and_then(|t| t.gpu_device());
+
+		GpuStatus {
+			// A fallback turns `use_gpu` off in the config
+			requested: self.config.lock().use_gpu || fell_back,
+			active: gpu_device.is_some(),
+			device_name: gpu_device.map(gpu_device_name),
+			fell_back,
+		}
+	}
+
+	pub fn record_readiness(&self) -> RecordReadiness {
+		let mut reasons = Vec::new();
+
+		if self.is_model_loading() {
+			reasons.push("Model is loading".to_string());
+		} else if !self.has_model() {
+			reasons.push("No model loaded".to_string());
+		}
+
+		match self.get_state() {
+			AppState::Idle => {}
+			AppState::Recording => reasons.push("Already recording".to_string()),
+			AppState::Transcribing => reasons.push("Transcription in progress".to_string()),
+			AppState::Calibrating => reasons.push("Measuring the noise floor".to_string()),
+		}
+
+		let audio_host = self.config.lock().audio_host.clone();
+		if !audio::has_input_device(audio_host.as_deref()) {
+			reasons.push("No microphone found".to_string());
+		}
+
+		RecordReadiness {
+			ready: reasons.is_empty(),
+			reasons,
+		}
+	}
+
 	pub fn set_error(&self, error: Option<String>) {
 		*self.error.lock() = error;
 	}
@@ -141,4 +620,217 @@ impl AppStateManager {
 	pub fn get_last_transcription(&self) -> String {
 		self.last_transcription.lock().clone()
 	}
+
+	pub fn set_last_recording(&self, samples: Vec<f32>) {
+		*self.last_recording.lock() = samples;
+	}
+
+	pub fn get_last_waveform(&self, buckets: usize) -> Vec<f32> {
+		audio::peak_envelope(&self.last_recording.lock(), buckets)
+	}
+
+	pub fn record_transcription(&self, audio_secs: f64, text: &str, transcription_secs: f64) {
+		if audio_secs >= MIN_TIMED_AUDIO_SECS
+			&& let Some(key) = self.realtime_factor_key()
+		{
+			let factor = (transcription_secs / audio_secs) as f32;
+			self.realtime_factors.lock().record(key, factor);
+		}
+
+		let mut stats = self.stats.lock();
+		stats.record(audio_secs, text, transcription_secs);
+		if let Err(e) = stats.save() {
+			eprintln!("Failed to save stats: {}", e);
+		}
+	}
+
+	fn realtime_factor_key(&self) -> Option<RealtimeFactorKey> {
+		let transcriber = self.transcriber.lock();
+		let transcriber = transcriber.as_ref()?;
+		Some((transcriber.model_path().to_string(), transcriber.gpu_device()))
+	}
+
+	/// Predicted seconds to transcribe `audio_secs` with the loaded model,
+	/// from recent runs or a per-size default before any have been timed
+	pub fn estimate_transcription_time(&self, audio_secs: f32) -> f32 {
+		let key = self.realtime_factor_key();
+		let factor = key
+			.as_ref()
+			.and_then(|key| self.realtime_factors.lock().average(key))
+			.unwrap_or_else(|| {
+				let model_type = key
+					.and_then(|(path, _)| ModelMetadata::read(Path::new(&path)).ok())
+					.map(|metadata| metadata.model_type)
+					.unwrap_or_default();
+				default_realtime_factor(&model_type)
+			});
+
+		audio_secs.max(0.0) * factor
+	}
+
+	pub fn get_locked_language(&self) -> Option<String> {
+		self.locked_language.lock().clone()
+	}
+
+	pub fn set_locked_language(&self, language: Option<String>) {
+		*self.locked_language.lock() = language;
+	}
+
+	/// Starts a timer that cancels one transcription through its own abort
+	/// flag once `timeout` passes, or when `cancel_transcription` is set.
+	/// Start it while holding the transcriber and call `finish` when done.
+	pub fn start_watchdog(self: &Arc<Self>, timeout: Duration) -> TranscriptionWatchdog {
+		let (done, done_rx) = mpsc::channel::<()>();
+		let cancel = Arc::new(AtomicBool::new(false));
+		let state = Arc::clone(self);
+		let run_cancel = Arc::clone(&cancel);
+		let handle = std::thread::spawn(move || {
+			let deadline = Instant::now() + timeout;
+			loop {
+				let left = deadline.saturating_duration_since(Instant::now());
+				if left.is_zero() {
+					run_cancel.store(true, Ordering::SeqCst);
+					return true;
+				}
+				if done_rx.recv_timeout(left.min(WATCHDOG_POLL_INTERVAL)) != Err(RecvTimeoutError::Timeout) {
+					return false;
+				}
+				if state.cancel_transcription.load(Ordering::SeqCst) {
+					run_cancel.store(true, Ordering::SeqCst);
+					return false;
+				}
+			}
+		});
+		TranscriptionWatchdog { cancel, done, handle }
+	}
+
+	pub fn mark_transcription_completed(&self) {
+		*self.last_completed.lock() = Some(Instant::now());
+	}
+
+	/// How long to wait before the next transcription may start
+	pub fn cooldown_remaining(&self, min_interval: Duration) -> Duration {
+		self.last_completed
+			.lock()
+			.map(|completed| min_interval.saturating_sub(completed.elapsed()))
+			.unwrap_or(Duration::ZERO)
+	}
+
+	pub fn add_history(&self, text: &str) {
+		let mut history = self.history.lock();
+		history.push(text);
+		if let Err(e) = history.save() {
+			eprintln!("Failed to save history: {}", e);
+		}
+	}
+
+	pub fn get_history(&self) -> Vec<HistoryEntry> {
+		self.history.lock().entries.clone()
+	}
+
+	pub fn export_history(&self, path: &str, format: &str) -> anyhow::Result<()> {
+		let format = ExportFormat::parse(format)?;
+		let content = self.history.lock().export(format)?;
+		std::fs::write(path, content)
+			.map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path, e))
+	}
+
+	pub fn get_stats(&self) -> Stats {
+		self.stats.lock().clone()
+	}
+
+	pub fn reset_stats(&self) -> anyhow::Result<()> {
+		let mut stats = self.stats.lock();
+		*stats = Stats::default();
+		stats.save()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Barrier;
+	use std::sync::atomic::AtomicUsize;
+
+	#[test]
+	fn test_realtime_factor_averages_recent_runs() {
+		let mut factors = RealtimeFactors::default();
+		let key = ("model.bin".to_string(), None);
+		assert_eq!(factors.average(&key), None);
+
+		for factor in [10.0, 1.0, 1.0, 1.0, 1.0, 1.0] {
+			factors.record(key.clone(), factor);
+		}
+		// The oldest run has dropped out of the window
+		assert_eq!(factors.average(&key), Some(1.0));
+		assert_eq!(factors.average(&("model.bin".to_string(), Some(0))), None);
+	}
+
+	#[test]
+	fn test_compare_and_set_requires_expected_state() {
+		let state = Mutex::new(AppState::Transcribing);
+		assert!(!compare_and_set(&state, AppState::Idle, AppState::Recording));
+		assert_eq!(*state.lock(), AppState::Transcribing);
+
+		assert!(compare_and_set(&state, AppState::Transcribing, AppState::Idle));
+		assert_eq!(*state.lock(), AppState::Idle);
+	}
+
+	#[test]
+	fn test_concurrent_starts_and_stops_keep_one_session() {
+		const THREADS: usize = 8;
+		const ROUNDS: usize = 500;
+		let state = Arc::new(Mutex::new(AppState::Idle));
+		let slot: Arc<Mutex<Option<usize>>> = Arc::new(Mutex::new(None));
+		let live_sessions = Arc::new(AtomicUsize::new(0));
+		let barrier = Arc::new(Barrier::new(THREADS));
+
+		// Half the threads act like the hotkey starting, half like a stop
+		let handles: Vec<_> = (0..THREADS)
+			.map(|thread| {
+				let (state, slot) = (Arc::clone(&state), Arc::clone(&slot));
+				let live_sessions = Arc::clone(&live_sessions);
+				let barrier = Arc::clone(&barrier);
+				std::thread::spawn(move || {
+					barrier.wait();
+					for round in 0..ROUNDS {
+						if thread % 2 == 0 {
+							let started = begin_session(&state, &slot, || {
+								let live = live_sessions.fetch_add(1, Ordering::SeqCst);
+								assert_eq!(live, 0, "A second session was started");
+								Ok::<_, ()>(round)
+							});
+							assert!(!matches!(started, Some(Err(_))));
+						} else if let Some(session) = end_session(&state, &slot) {
+							assert!(session.is_some(), "Recording without a stored session");
+							live_sessions.fetch_sub(1, Ordering::SeqCst);
+							// Transcription done
+							*state.lock() = AppState::Idle;
+						}
+					}
+				})
+			})
+			.collect();
+
+		for handle in handles {
+			handle.join().unwrap();
+		}
+
+		let remaining = slot.lock().is_some() as usize;
+		assert_eq!(live_sessions.load(Ordering::SeqCst), remaining);
+	}
+
+	#[test]
+	fn test_failed_start_returns_to_idle() {
+		let state = Mutex::new(AppState::Idle);
+		let slot: Mutex<Option<()>> = Mutex::new(None);
+
+		let result = begin_session(&state, &slot, || Err("no device"));
+		assert_eq!(result, Some(Err("no device")));
+		assert_eq!(*state.lock(), AppState::Idle);
+
+		*state.lock() = AppState::Transcribing;
+		assert_eq!(begin_session(&state, &slot, || Ok::<_, ()>(())), None);
+		assert_eq!(end_session(&state, &slot), None);
+	}
 }
@@ -0,0 +1,119 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Usage totals accumulated across sessions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+	pub total_recordings: u64,
+	pub total_audio_secs: f64,
+	pub total_words: u64,
+	pub total_transcription_secs: f64,
+}
+
+impl Stats {
+	pub fn stats_path() -> Result<PathBuf> {
+		Ok(Config::config_dir()?.join("stats.json"))
+	}
+
+	pub fn load() -> Result<Self> {
+		let stats_path = Self::stats_path()?;
+
+		if stats_path.exists() {
+			let content = fs::read_to_string(&stats_path).context("Failed to read stats file")?;
+			serde_json::from_str(&content).context("Failed to parse stats file")
+		} else {
+			Ok(Stats::default())
+		}
+	}
+
+	pub fn save(&self) -> Result<()> {
+		let content = serde_json::to_string_pretty(self).context("Failed to serialize stats")?;
+		fs::write(Self::stats_path()?, content).context("Failed to write stats file")?;
+		Ok(())
+	}
+
+	pub fn record(&mut self, audio_secs: f64, text: &str, transcription_secs: f64) {
+		self.total_recordings += 1;
+		self.total_audio_secs += audio_secs;
+		self.total_words += count_words(text) as u64;
+		self.total_transcription_secs += transcription_secs;
+	}
+}
+
+pub fn count_words(text: &str) -> usize {
+	text.split_whitespace().count()
+}
+
+/// Smallest word count of each length bucket, the last one open-ended
+const LENGTH_BUCKET_STARTS: [usize; 5] = [1, 6, 16, 51, 151];
+
+/// Number of transcriptions with a word count in `min_words..=max_words`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LengthBucket {
+	pub min_words: usize,
+	/// `None` for the last bucket, which has no upper bound
+	pub max_words: Option<usize>,
+	pub count: usize,
+}
+
+/// Buckets `texts` by word count, from short commands to long notes.
+/// Empty texts are left out.
+pub fn length_histogram<'a>(texts: impl IntoIterator<Item = &'a str>) -> Vec<LengthBucket> {
+	let mut buckets: Vec<LengthBucket> = LENGTH_BUCKET_STARTS
+		.iter()
+		.enumerate()
+		.map(|(i, &min_words)| LengthBucket {
+			min_words,
+			max_words: LENGTH_BUCKET_STARTS.get(i + 1).map(|next| next - 1),
+			count: 0,
+		})
+		.collect();
+
+	for text in texts {
+		let words = count_words(text);
+		if let Some(bucket) = buckets.iter_mut().rev().find(|bucket| bucket.min_words <= words) {
+			bucket.count += 1;
+		}
+	}
+
+	buckets
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_count_words() {
+		assert_eq!(count_words(""), 0);
+		assert_eq!(count_words("  hello   world\n again "), 3);
+	}
+
+	#[test]
+	fn test_length_histogram() {
+		let long = "word ".repeat(200);
+		let histogram = length_histogram(["", "stop", "open the editor now please ok", &long]);
+
+		let counts: Vec<usize> = histogram.iter().map(|bucket| bucket.count).collect();
+		assert_eq!(counts, [1, 1, 0, 0, 1]);
+		assert_eq!(histogram[1].min_words, 6);
+		assert_eq!(histogram[1].max_words, Some(15));
+		assert_eq!(histogram[4].max_words, None);
+	}
+
+	#[test]
+	fn test_record_accumulates() {
+		let mut stats = Stats::default();
+		stats.record(2.5, "one two", 0.5);
+		stats.record(1.5, "three", 0.25);
+
+		assert_eq!(stats.total_recordings, 2);
+		assert_eq!(stats.total_words, 3);
+		assert_eq!(stats.total_audio_secs, 4.0);
+		assert_eq!(stats.total_transcription_secs, 0.75);
+	}
+}
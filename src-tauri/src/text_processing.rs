@@ -0,0 +1,618 @@
+use crate::config::OutputFormat;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Reads a wordlist file with one term per line, skipping blank lines and
+/// lines starting with `#`.
+pub fn load_wordlist(path: &Path) -> Result<Vec<String>> {
+	let content = fs::read_to_string(path)
+		.with_context(|| format!("Failed to read wordlist: {}", path.display()))?;
+
+	Ok(content
+		.lines()
+		.map(|line| line.trim())
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.map(|line| line.to_string())
+		.collect())
+}
+
+/// Token budget for a vocabulary prompt. Whisper keeps at most 224 tokens
+/// of prompt and drops the start of anything longer, which is where the
+/// most important terms are, so this leaves room for the prompt marker.
+pub const MAX_VOCABULARY_PROMPT_TOKENS: usize = 220;
+
+/// Joins vocabulary terms into an initial prompt of at most `max_tokens`
+/// as counted by `count_tokens`. Terms earlier in the list take priority,
+/// duplicates are dropped and terms that no longer fit are left out.
+pub fn vocabulary_prompt(
+	terms: &[String],
+	max_tokens: usize,
+	count_tokens: impl Fn(&str) -> usize,
+) -> String {
+	let mut seen = HashSet::new();
+	let mut prompt = String::new();
+
+	for term in terms {
+		if !seen.insert(term.to_lowercase()) {
+			continue;
+		}
+		let separator = if prompt.is_empty() { "" } else { ", " };
+		let candidate = format!("{}{}{}", prompt, separator, term);
+		if count_tokens(&candidate) > max_tokens {
+			break;
+		}
+		prompt = candidate;
+	}
+
+	prompt
+}
+
+/// Replaces words that are a close misspelling of a wordlist term with the
+/// term itself. Only unambiguous, close matches are corrected so common
+/// words are left alone.
+pub fn correct_terms(text: &str, wordlist: &[String]) -> String {
+	if wordlist.is_empty() {
+		return text.to_string();
+	}
+
+	let terms: Vec<(String, &str)> = wordlist
+		.iter()
+		.map(|term| (term.to_lowercase(), term.as_str()))
+		.collect();
+
+	let mut result = String::with_capacity(text.len());
+	let mut rest = text;
+
+	while !rest.is_empty() {
+		let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+		let (word, tail) = rest.split_at(word_end);
+		result.push_str(&correct_word(word, &terms));
+
+		let space_end = tail
+			.find(|c: char| !c.is_whitespace())
+			.unwrap_or(tail.len());
+		result.push_str(&tail[..space_end]);
+		rest = &tail[space_end..];
+	}
+
+	result
+}
+
+fn correct_word(word: &str, terms: &[(String, &str)]) -> String {
+	let start = word
+		.find(|c: char| c.is_alphanumeric())
+		.unwrap_or(word.len());
+	let end = word
+		.rfind(|c: char| c.is_alphanumeric())
+		.map(|i| i + word[i..].chars().next().map_or(1, char::len_utf8))
+		.unwrap_or(start);
+
+	if start >= end {
+		return word.to_string();
+	}
+
+	let core = &word[start..end];
+	let lowered = core.to_lowercase();
+	let max_distance = max_correction_distance(lowered.chars().count());
+
+	let mut best: Option<(usize, &str)> = None;
+	let mut ambiguous = false;
+
+	for (term_lower, term) in terms {
+		let distance = levenshtein(&lowered, term_lower);
+		if distance > max_distance {
+			continue;
+		}
+		// Misheard words almost always keep their first sound
+		if distance > 0 && lowered.chars().next() != term_lower.chars().next() {
+			continue;
+		}
+
+		match best {
+			Some((best_distance, best_term)) if distance == best_distance => {
+				if best_term != *term {
+					ambiguous = true;
+				}
+			}
+			Some((best_distance, _)) if distance > best_distance => {}
+			_ => {
+				best = Some((distance, term));
+				ambiguous = false;
+			}
+		}
+	}
+
+	match best {
+		Some((_, term)) if !ambiguous => format!("{}{}{}", &word[..start], term, &word[end..]),
+		_ => word.to_string(),
+	}
+}
+
+/// Rewrites whole words matching an acronym regardless of case into its
+/// canonical form, e.g. "the api" into "the API". Words are runs of
+/// letters and digits, so "Api-key" and "url's" match too.
+pub fn apply_acronyms(text: &str, acronyms: &[String]) -> String {
+	if acronyms.is_empty() {
+		return text.to_string();
+	}
+
+	let mut result = String::with_capacity(text.len());
+	let mut rest = text;
+
+	while let Some(start) = rest.find(char::is_alphanumeric) {
+		result.push_str(&rest[..start]);
+		let word_rest = &rest[start..];
+		let end = word_rest
+			.find(|c: char| !c.is_alphanumeric())
+			.unwrap_or(word_rest.len());
+		let word = &word_rest[..end];
+
+		let canonical = acronyms
+			.iter()
+			.find(|acronym| acronym.to_lowercase() == word.to_lowercase());
+		result.push_str(canonical.map_or(word, String::as_str));
+		rest = &word_rest[end..];
+	}
+	result.push_str(rest);
+
+	result
+}
+
+/// Prefixes the text with its language code, e.g. "[it] ciao". Empty text
+/// stays empty.
+pub fn tag_language(text: &str, language: &str) -> String {
+	if text.is_empty() {
+		return String::new();
+	}
+	format!("[{}] {}", language, text)
+}
+
+/// `text` without the tag `tag_language` put in front of it. Only language
+/// codes count, so markers like "[laughs]" are kept.
+pub fn strip_language_tag(text: &str) -> &str {
+	let Some((tag, rest)) = text.strip_prefix('[').and_then(|text| text.split_once("] ")) else {
+		return text;
+	};
+	let is_code = (2..=3).contains(&tag.len()) && tag.bytes().all(|b| b.is_ascii_lowercase());
+	if is_code { rest } else { text }
+}
+
+/// Languages that don't end sentences with a mark, or end them with one
+/// other than a period
+fn sentence_terminator(language: Option<&str>) -> Option<char> {
+	match language {
+		Some("zh" | "ja") => Some('。'),
+		Some("th" | "lo" | "km" | "my") => None,
+		Some("hi" | "mr" | "ne" | "bn") => Some('।'),
+		_ => Some('.'),
+	}
+}
+
+/// Appends a sentence terminator unless the text already ends with
+/// punctuation. `language` picks the terminator and skips languages that
+/// don't use one.
+pub fn ensure_end_punctuation(text: &str, language: Option<&str>) -> String {
+	let trimmed = text.trim_end();
+	let Some(last) = trimmed.chars().last() else {
+		return text.to_string();
+	};
+
+	let has_punctuation = last.is_ascii_punctuation() || "…。！？؟।」』".contains(last);
+	match sentence_terminator(language) {
+		Some(terminator) if !has_punctuation => format!("{}{}", trimmed, terminator),
+		_ => text.to_string(),
+	}
+}
+
+/// Abbreviations whose period doesn't end a sentence, lowercased
+const ABBREVIATIONS: &[&str] = &[
+	"mr.", "mrs.", "ms.", "dr.", "prof.", "sr.", "jr.", "st.", "vs.", "no.", "approx.", "fig.",
+	"sig.", "dott.", "ing.", "avv.", "pag.", "ecc.",
+];
+
+/// Whether the word ending in a period is an abbreviation rather than the
+/// end of a sentence: a known one, a single initial like "J." or one with
+/// inner periods like "e.g."
+fn is_abbreviation(word: &str) -> bool {
+	let word = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+	let stem = word.trim_end_matches('.');
+	ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+		|| (stem.chars().count() == 1 && stem.chars().all(char::is_alphabetic))
+		|| (stem.contains('.') && !stem.contains(".."))
+}
+
+/// Splits text into sentences on terminal punctuation and line breaks,
+/// without breaking after abbreviations such as "e.g." or "Dr."
+pub fn split_sentences(text: &str) -> Vec<String> {
+	let mut sentences = Vec::new();
+
+	for line in text.lines() {
+		let chars: Vec<char> = line.chars().collect();
+		let mut start = 0;
+		let mut i = 0;
+
+		while i < chars.len() {
+			let c = chars[i];
+			if !".!?…。！？".contains(c) {
+				i += 1;
+				continue;
+			}
+
+			// Take any run of terminators and closing quotes with it
+			let mut end = i + 1;
+			while end < chars.len() && ".!?…。！？\"'”’»)]」』".contains(chars[end]) {
+				end += 1;
+			}
+
+			// Full-width marks end a sentence without a following space
+			let at_break = end == chars.len()
+				|| chars[end].is_whitespace()
+				|| "。！？".contains(chars[end - 1]);
+			let word: String = chars[start..end]
+				.iter()
+				.rev()
+				.take_while(|c| !c.is_whitespace())
+				.collect::<Vec<_>>()
+				.into_iter()
+				.rev()
+				.collect();
+
+			if at_break && !(c == '.' && end == i + 1 && is_abbreviation(&word)) {
+				let sentence: String = chars[start..end].iter().collect();
+				if !sentence.trim().is_empty() {
+					sentences.push(sentence.trim().to_string());
+				}
+				start = end;
+			}
+			i = end;
+		}
+
+		let rest: String = chars[start..].iter().collect();
+		if !rest.trim().is_empty() {
+			sentences.push(rest.trim().to_string());
+		}
+	}
+
+	sentences
+}
+
+/// Splits a command line into arguments on whitespace, keeping quoted
+/// sections together. No shell features such as pipes or variables apply.
+pub fn split_command_line(command: &str) -> Vec<String> {
+	let mut args = Vec::new();
+	let mut current = String::new();
+	let mut in_arg = false;
+	let mut quote = None;
+
+	for c in command.chars() {
+		match (quote, c) {
+			(Some(q), c) if c == q => quote = None,
+			(Some(_), c) => current.push(c),
+			(None, '"' | '\'') => {
+				quote = Some(c);
+				in_arg = true;
+			}
+			(None, c) if c.is_whitespace() => {
+				if in_arg {
+					args.push(std::mem::take(&mut current));
+					in_arg = false;
+				}
+			}
+			(None, c) => {
+				current.push(c);
+				in_arg = true;
+			}
+		}
+	}
+
+	if in_arg {
+		args.push(current);
+	}
+	args
+}
+
+/// Texts shorter than this are too short for the repetition ratio to mean much
+const MIN_WORDS_FOR_REPETITION: usize = 8;
+
+/// Number of words per distinct word, ignoring case and punctuation. A
+/// looping transcription like "you you you ..." scores high, `None` when
+/// the text is too short to judge.
+pub fn repetition_ratio(text: &str) -> Option<f32> {
+	let words: Vec<String> = text
+		.split_whitespace()
+		.map(|word| {
+			word.trim_matches(|c: char| !c.is_alphanumeric())
+				.to_lowercase()
+		})
+		.filter(|word| !word.is_empty())
+		.collect();
+
+	if words.len() < MIN_WORDS_FOR_REPETITION {
+		return None;
+	}
+
+	let distinct: HashSet<&str> = words.iter().map(String::as_str).collect();
+	Some(words.len() as f32 / distinct.len() as f32)
+}
+
+/// Renders text as HTML for the clipboard: escaped, with blank lines
+/// separating paragraphs and single line breaks kept as `<br>`
+pub fn to_clipboard_html(text: &str) -> String {
+	let escaped = text
+		.trim_end()
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;");
+
+	escaped
+		.split("\n\n")
+		.filter(|paragraph| !paragraph.trim().is_empty())
+		.map(|paragraph| format!("<p>{}</p>", paragraph.trim().replace('\n', "<br>")))
+		.collect()
+}
+
+/// Applies the output format line by line. Blank lines stay blank in a
+/// bullet list and become bare `>` inside a quote so it isn't split.
+pub fn format_output(text: &str, format: OutputFormat) -> String {
+	let prefix_lines = |prefix: &str, blank: &str| {
+		text.lines()
+			.map(|line| {
+				if line.trim().is_empty() {
+					blank.to_string()
+				} else {
+					format!("{}{}", prefix, line.trim_start())
+				}
+			})
+			.collect::<Vec<_>>()
+			.join("\n")
+	};
+
+	match format {
+		OutputFormat::Plain => text.to_string(),
+		OutputFormat::MarkdownQuote => prefix_lines("> ", ">"),
+		OutputFormat::Bullet => prefix_lines("- ", ""),
+	}
+}
+
+/// Replaces `{name}` placeholders with the matching value. Placeholders
+/// without a value and unmatched braces are kept as written.
+pub fn render_template(template: &str, values: &[(&str, &str)]) -> String {
+	let mut result = String::with_capacity(template.len());
+	let mut rest = template;
+
+	while let Some(open) = rest.find('{') {
+		result.push_str(&rest[..open]);
+		let after = &rest[open + 1..];
+
+		match after.find(['{', '}']) {
+			Some(close) if after.as_bytes()[close] == b'}' => {
+				let name = &after[..close];
+				match values.iter().find(|(key, _)| *key == name) {
+					Some((_, value)) => result.push_str(value),
+					None => {
+						result.push('{');
+						result.push_str(name);
+						result.push('}');
+					}
+				}
+				rest = &after[close + 1..];
+			}
+			_ => {
+				result.push('{');
+				rest = after;
+			}
+		}
+	}
+
+	result.push_str(rest);
+	result
+}
+
+/// Short words are only fixed for casing, longer ones tolerate more typos
+fn max_correction_distance(len: usize) -> usize {
+	match len {
+		0..=4 => 0,
+		5..=8 => 1,
+		_ => 2,
+	}
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+	let a: Vec<char> = a.chars().collect();
+	let b: Vec<char> = b.chars().collect();
+
+	let mut previous: Vec<usize> = (0..=b.len()).collect();
+	let mut current = vec![0; b.len() + 1];
+
+	for (i, ca) in a.iter().enumerate() {
+		current[0] = i + 1;
+		for (j, cb) in b.iter().enumerate() {
+			let cost = if ca == cb { 0 } else { 1 };
+			current[j + 1] = (previous[j + 1] + 1)
+				.min(current[j] + 1)
+				.min(previous[j] + cost);
+		}
+		std::mem::swap(&mut previous, &mut current);
+	}
+
+	previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_apply_acronyms_whole_words() {
+		let acronyms = vec!["API".to_string(), "NASA".to_string(), "iOS".to_string()];
+		assert_eq!(
+			apply_acronyms("The api from Nasa, on ios. Api-key!", &acronyms),
+			"The API from NASA, on iOS. API-key!"
+		);
+		// Only whole words
+		assert_eq!(apply_acronyms("rapid apis", &acronyms), "rapid apis");
+		assert_eq!(apply_acronyms("api", &[]), "api");
+	}
+
+	#[test]
+	fn test_to_clipboard_html() {
+		assert_eq!(
+			to_clipboard_html("Use <b> & \"quotes\"\nnext line\n\nNew paragraph\n"),
+			"<p>Use &lt;b&gt; &amp; &quot;quotes&quot;<br>next line</p><p>New paragraph</p>"
+		);
+		assert_eq!(to_clipboard_html("  \n"), "");
+	}
+
+	#[test]
+	fn test_vocabulary_prompt_dedupes_and_truncates() {
+		let terms = wordlist(&["Kubernetes", "kubernetes", "etcd", "Prometheus", "Grafana"]);
+		assert_eq!(vocabulary_prompt(&terms, 100, str::len), "Kubernetes, etcd, Prometheus, Grafana");
+		// Stops at the first term that doesn't fit, keeping the earlier ones
+		assert_eq!(vocabulary_prompt(&terms, 20, str::len), "Kubernetes, etcd");
+		assert_eq!(vocabulary_prompt(&terms, 5, str::len), "");
+
+		// Counted in tokens, here one per word
+		let words = |text: &str| text.split_whitespace().count();
+		assert_eq!(vocabulary_prompt(&terms, 3, words), "Kubernetes, etcd, Prometheus");
+	}
+
+	#[test]
+	fn test_split_sentences_on_punctuation_and_lines() {
+		assert_eq!(
+			split_sentences("Hi there! How are you? Fine.\nSee you"),
+			vec!["Hi there!", "How are you?", "Fine.", "See you"]
+		);
+		assert_eq!(split_sentences("He said \"stop.\" Then left."), vec!["He said \"stop.\"", "Then left."]);
+		assert_eq!(split_sentences("你好。再见。"), vec!["你好。", "再见。"]);
+		assert!(split_sentences("  ").is_empty());
+	}
+
+	#[test]
+	fn test_split_sentences_keeps_abbreviations() {
+		assert_eq!(
+			split_sentences("Bring fruit, e.g. apples. Ask Dr. Rossi about J. Smith."),
+			vec!["Bring fruit, e.g. apples.", "Ask Dr. Rossi about J. Smith."]
+		);
+		assert_eq!(split_sentences("It costs 3.50 euros. Cheap!"), vec!["It costs 3.50 euros.", "Cheap!"]);
+		assert_eq!(split_sentences("Wait... what?"), vec!["Wait...", "what?"]);
+	}
+
+	fn wordlist(terms: &[&str]) -> Vec<String> {
+		terms.iter().map(|t| t.to_string()).collect()
+	}
+
+	#[test]
+	fn test_levenshtein() {
+		assert_eq!(levenshtein("", ""), 0);
+		assert_eq!(levenshtein("abc", ""), 3);
+		assert_eq!(levenshtein("kitten", "sitting"), 3);
+		assert_eq!(levenshtein("tauri", "tauri"), 0);
+	}
+
+	#[test]
+	fn test_correct_terms_fixes_close_match() {
+		let terms = wordlist(&["Kubernetes", "Brodino"]);
+		assert_eq!(
+			correct_terms("deploy it on kubernetis, ask brodimo", &terms),
+			"deploy it on Kubernetes, ask Brodino"
+		);
+	}
+
+	#[test]
+	fn test_correct_terms_leaves_distant_words() {
+		let terms = wordlist(&["Kubernetes"]);
+		assert_eq!(correct_terms("the cat sat", &terms), "the cat sat");
+	}
+
+	#[test]
+	fn test_correct_terms_short_words_only_fix_casing() {
+		let terms = wordlist(&["Rust"]);
+		assert_eq!(correct_terms("rust and bust", &terms), "Rust and bust");
+
+		let terms = wordlist(&["API"]);
+		assert_eq!(correct_terms("an api or ape", &terms), "an API or ape");
+	}
+
+	#[test]
+	fn test_correct_terms_skips_ambiguous_matches() {
+		let terms = wordlist(&["Marian", "Marion"]);
+		assert_eq!(correct_terms("Marien", &terms), "Marien");
+	}
+
+	#[test]
+	fn test_render_template_substitutes_known_placeholders() {
+		let values = [("text", "hello there"), ("lang", "en"), ("time", "2024-01-01T00:00:00Z")];
+		assert_eq!(
+			render_template("{time} [{lang}] {text}", &values),
+			"2024-01-01T00:00:00Z [en] hello there"
+		);
+	}
+
+	#[test]
+	fn test_render_template_keeps_unknown_and_unbalanced() {
+		let values = [("text", "hi")];
+		assert_eq!(render_template("{user}: {text}", &values), "{user}: hi");
+		assert_eq!(render_template("a { b {text}", &values), "a { b hi");
+		assert_eq!(render_template("{text", &values), "{text");
+	}
+
+	#[test]
+	fn test_split_command_line() {
+		assert_eq!(
+			split_command_line(r#"notify-send "New note" '{text}'  --urgency=low"#),
+			vec!["notify-send", "New note", "{text}", "--urgency=low"]
+		);
+		assert_eq!(split_command_line(r#"cmd "" x"#), vec!["cmd", "", "x"]);
+		assert!(split_command_line("   ").is_empty());
+	}
+
+	#[test]
+	fn test_tag_language() {
+		assert_eq!(tag_language("ciao a tutti", "it"), "[it] ciao a tutti");
+		assert_eq!(tag_language("", "it"), "");
+		assert_eq!(strip_language_tag("[it] ciao a tutti"), "ciao a tutti");
+		assert_eq!(strip_language_tag("[laughs] ok then"), "[laughs] ok then");
+		assert_eq!(strip_language_tag("no tag"), "no tag");
+	}
+
+	#[test]
+	fn test_ensure_end_punctuation() {
+		assert_eq!(ensure_end_punctuation("take notes  ", Some("en")), "take notes.");
+		assert_eq!(ensure_end_punctuation("really?", None), "really?");
+		assert_eq!(ensure_end_punctuation("", None), "");
+		assert_eq!(ensure_end_punctuation("你好", Some("zh")), "你好。");
+		assert_eq!(ensure_end_punctuation("สวัสดี", Some("th")), "สวัสดี");
+	}
+
+	#[test]
+	fn test_repetition_ratio() {
+		assert_eq!(repetition_ratio("you you you"), None);
+		assert_eq!(repetition_ratio("You, you. you you you you you you"), Some(8.0));
+
+		let ratio = repetition_ratio("the quick brown fox jumps over the lazy dog").unwrap();
+		assert!(ratio < 1.2);
+	}
+
+	#[test]
+	fn test_format_output_markdown_quote() {
+		assert_eq!(
+			format_output("first line\n\n second", OutputFormat::MarkdownQuote),
+			"> first line\n>\n> second"
+		);
+	}
+
+	#[test]
+	fn test_format_output_bullet_and_plain() {
+		assert_eq!(format_output("one\ntwo", OutputFormat::Bullet), "- one\n- two");
+		assert_eq!(format_output("one\ntwo", OutputFormat::Plain), "one\ntwo");
+	}
+
+	#[test]
+	fn test_correct_terms_preserves_whitespace() {
+		let terms = wordlist(&["Whisper"]);
+		assert_eq!(correct_terms(" wisper\n  ok ", &terms), " Whisper\n  ok ");
+	}
+}
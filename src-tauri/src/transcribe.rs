@@ -1,13 +1,123 @@
+use crate::audio::TRANSCRIPTION_SAMPLE_RATE;
+use crate::config::{self, Config, ModelMetadata, ModelSettings, TimestampFormat};
 use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::ffi::c_void;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use whisper_rs::{
-	get_lang_max_id, get_lang_str, get_lang_str_full, FullParams, SamplingStrategy, WhisperContext,
-	WhisperContextParameters,
+	get_lang_id, get_lang_max_id, get_lang_str, get_lang_str_full, FullParams, SamplingStrategy, WhisperContext,
+	WhisperContextParameters, WhisperState,
 };
 
+/// Wait before retrying a failed state allocation, giving the GPU driver
+/// a moment to release memory
+const STATE_RETRY_DELAY: Duration = Duration::from_millis(250);
+
 pub struct Transcriber {
 	ctx: WhisperContext,
 	is_multilingual: bool,
+	model_path: String,
+	gpu_device: Option<i32>,
+	/// State kept between transcriptions when carrying context over
+	context_state: Mutex<Option<WhisperState>>,
+}
+
+/// Decoding settings for a single transcription
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TranscribeOptions<'a> {
+	/// Language code, `None` or "auto" to auto-detect
+	pub language: Option<&'a str>,
+	/// Condition decoding on the text of the previous transcription.
+	///
+	/// Whisper always carries context across the 30 second windows of a
+	/// single recording; this extends that to consecutive recordings. When
+	/// an initial prompt is also set, Whisper places it before the carried
+	/// text and drops it first once the context budget (half the model's
+	/// text context) is exceeded, so a long history can crowd the prompt out.
+	pub carry_context: bool,
+	/// Start of the audio to transcribe, in milliseconds
+	pub offset_ms: u32,
+	/// Length of the audio to transcribe in milliseconds, 0 runs to the end
+	pub duration_ms: u32,
+	/// Prefix each segment with its start time, one segment per line
+	pub timestamps: Option<TimestampFormat>,
+	/// Segments more likely than this to contain no speech are dropped
+	pub no_speech_threshold: Option<f32>,
+	/// Initial sampling temperature
+	pub temperature: f32,
+	/// Temperature step for retries after a failed decode, 0.0 disables them
+	pub temperature_inc: f32,
+	/// Entropy below which a decode is considered repetitive and retried
+	pub entropy_threshold: f32,
+	/// Average log probability below which a decode is retried
+	pub logprob_threshold: f32,
+	/// With timestamps, a pause at least this long between segments is
+	/// marked with a `---` line as a likely change of speaker
+	pub speaker_gap_ms: Option<u32>,
+	/// Per-model thread counts, keyed by model name
+	pub model_settings: Option<&'a BTreeMap<String, ModelSettings>>,
+	/// Thread count for models without their own, `None` for one per core
+	pub default_threads: Option<i32>,
+	/// Thread count to decode with, overriding the two above
+	pub n_threads: Option<i32>,
+	/// Text decoding is primed with, such as a vocabulary list
+	pub initial_prompt: Option<&'a str>,
+}
+
+/// Timing of one run of `Transcriber::benchmark_threads`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ThreadBench {
+	pub n_threads: i32,
+	pub duration_ms: u64,
+	/// Processing time divided by audio duration, lower is faster
+	pub realtime_factor: f64,
+}
+
+/// Text of a transcription along with what was left out of it
+#[derive(Debug, Clone, Default)]
+pub struct Transcription {
+	pub text: String,
+	/// Segments dropped for exceeding the no-speech threshold
+	pub filtered_segments: usize,
+	/// Language code the audio was decoded as, detected or forced
+	pub language: Option<String>,
+	/// Set when the decoding state couldn't be created on the first try
+	pub recovery: Option<StateRecovery>,
+}
+
+/// How a transcription recovered from failing to create its state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateRecovery {
+	/// The second attempt succeeded
+	Retried,
+	/// Both attempts failed on the GPU, so it ran on a CPU copy of the model
+	CpuFallback,
+}
+
+impl<'a> TranscribeOptions<'a> {
+	pub fn from_config(config: &'a Config) -> Self {
+		Self {
+			language: Some(config.language.as_str()),
+			carry_context: config.carry_context,
+			offset_ms: 0,
+			duration_ms: 0,
+			timestamps: config.inline_timestamps.then_some(config.timestamp_format),
+			no_speech_threshold: config.no_speech_threshold,
+			temperature: config.temperature,
+			temperature_inc: config.temperature_inc,
+			entropy_threshold: config.entropy_threshold,
+			logprob_threshold: config.logprob_threshold,
+			speaker_gap_ms: config.speaker_gap_ms,
+			model_settings: Some(&config.model_settings),
+			default_threads: config.n_threads,
+			n_threads: None,
+			initial_prompt: config.initial_prompt.as_deref().filter(|prompt| !prompt.trim().is_empty()),
+		}
+	}
 }
 
 /// Result of loading a transcriber, includes whether GPU fallback occurred
@@ -24,6 +134,8 @@ impl Transcriber {
 		if !path.exists() {
 			anyhow::bail!("Model file not found: {}", model_path);
 		}
+		// Catches renamed or truncated files before whisper.cpp tries to load them
+		ModelMetadata::read(path)?.check_supported()?;
 
 		let mut params = WhisperContextParameters::default();
 		params.use_gpu(use_gpu);
@@ -52,13 +164,48 @@ impl Transcriber {
 			transcriber: Self {
 				ctx,
 				is_multilingual,
+				model_path: model_path.to_string(),
+				gpu_device: (use_gpu && !gpu_fallback).then_some(gpu_device),
+				context_state: Mutex::new(None),
 			},
 			gpu_fallback,
 		})
 	}
 
-	pub fn transcribe(&self, samples: &[f32], language: Option<&str>) -> Result<String> {
-		let mut state = self.ctx.create_state().context("Failed to create state")?;
+	/// Runs a transcription. Setting `cancel` while it runs aborts the
+	/// decoding and returns an error.
+	pub fn transcribe(
+		&self,
+		samples: &[f32],
+		options: &TranscribeOptions,
+		cancel: &AtomicBool,
+	) -> Result<String> {
+		self.transcribe_detailed(samples, options, cancel)
+			.map(|transcription| transcription.text)
+	}
+
+	/// Like `transcribe`, also reporting how many segments were filtered out
+	pub fn transcribe_detailed(
+		&self,
+		samples: &[f32],
+		options: &TranscribeOptions,
+		cancel: &AtomicBool,
+	) -> Result<Transcription> {
+		let mut context_state = self.context_state.lock();
+		let (mut state, recovery) = match context_state.take() {
+			Some(state) if options.carry_context => (state, None),
+			_ => match self.create_state() {
+				Ok((state, retried)) => (state, retried.then_some(StateRecovery::Retried)),
+				Err(e) if self.gpu_device.is_some() => {
+					eprintln!("{:#}, transcribing on the CPU instead", e);
+					let cpu = Transcriber::new(&self.model_path, false, 0)?.transcriber;
+					let mut transcription = cpu.transcribe_detailed(samples, options, cancel)?;
+					transcription.recovery = Some(StateRecovery::CpuFallback);
+					return Ok(transcription);
+				}
+				Err(e) => return Err(e),
+			},
+		};
 
 		let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
 
@@ -69,9 +216,19 @@ impl Transcriber {
 		params.set_suppress_blank(true);
 		params.set_suppress_nst(true);
 		params.set_translate(false);
+		params.set_no_context(!options.carry_context);
+		params.set_offset_ms(options.offset_ms as i32);
+		params.set_duration_ms(options.duration_ms as i32);
+		params.set_temperature(options.temperature);
+		params.set_temperature_inc(options.temperature_inc);
+		params.set_entropy_thold(options.entropy_threshold);
+		params.set_logprob_thold(options.logprob_threshold);
+		if let Some(prompt) = options.initial_prompt {
+			params.set_initial_prompt(prompt);
+		}
 
 		if self.is_multilingual {
-			if let Some(lang) = language {
+			if let Some(lang) = options.language {
 				if lang != "auto" {
 					params.set_language(Some(lang));
 				}
@@ -80,29 +237,228 @@ impl Transcriber {
 			params.set_language(Some("en"));
 		}
 
-		params.set_n_threads(num_cpus());
+		let threads = options
+			.n_threads
+			.or_else(|| {
+				config::model_threads(
+					options.model_settings.unwrap_or(&BTreeMap::new()),
+					options.default_threads,
+					&self.model_path,
+				)
+			})
+			.unwrap_or_else(num_cpus);
+		params.set_n_threads(threads);
 
-		state
-			.full(params, samples)
-			.context("Failed to run transcription")?;
+		// SAFETY: `cancel` outlives the `full` call below, which is the only
+		// place the callback can be invoked from.
+		unsafe {
+			params.set_abort_callback(Some(abort_callback));
+			params.set_abort_callback_user_data(cancel as *const AtomicBool as *mut c_void);
+		}
+
+		if let Err(e) = state.full(params, samples) {
+			if cancel.load(Ordering::SeqCst) {
+				anyhow::bail!("Transcription cancelled");
+			}
+			return Err(e).context("Failed to run transcription");
+		}
 
 		let num_segments = state.full_n_segments();
 		let mut result = String::new();
+		let mut filtered_segments = 0;
+		let mut previous_end = None;
 
 		for i in 0..num_segments {
 			if let Some(segment) = state.get_segment(i) {
+				if let Some(threshold) = options.no_speech_threshold
+					&& segment.no_speech_probability() > threshold
+				{
+					filtered_segments += 1;
+					continue;
+				}
 				if let Ok(text) = segment.to_str_lossy() {
-					result.push_str(&text);
+					match options.timestamps {
+						Some(format) => {
+							let text = text.trim();
+							if text.is_empty() {
+								continue;
+							}
+							if !result.is_empty() {
+								result.push('\n');
+							}
+							if let (Some(gap_ms), Some(end)) = (options.speaker_gap_ms, previous_end)
+								&& is_speaker_gap(end, segment.start_timestamp(), gap_ms)
+							{
+								result.push_str("---\n");
+							}
+							previous_end = Some(segment.end_timestamp());
+							result.push_str(&format!(
+								"[{}] {}",
+								format_segment_time(segment.start_timestamp(), format),
+								text
+							));
+						}
+						None => result.push_str(&text),
+					}
 				}
 			}
 		}
 
-		Ok(result.trim().to_string())
+		let language = get_lang_str(state.full_lang_id_from_state()).map(str::to_string);
+
+		if options.carry_context {
+			*context_state = Some(state);
+		}
+
+		Ok(Transcription {
+			text: result.trim().to_string(),
+			filtered_segments,
+			language,
+			recovery,
+		})
+	}
+
+	/// Creates a decoding state, retrying once after a short wait since
+	/// allocation can fail while GPU memory is briefly exhausted. Returns
+	/// whether the retry was needed.
+	fn create_state(&self) -> Result<(WhisperState, bool)> {
+		match self.ctx.create_state() {
+			Ok(state) => Ok((state, false)),
+			Err(e) => {
+				eprintln!("Failed to create state: {}, retrying", e);
+				std::thread::sleep(STATE_RETRY_DELAY);
+				let state = self.ctx.create_state().context("Failed to create state")?;
+				Ok((state, true))
+			}
+		}
+	}
+
+	/// Detects which of `candidates` is spoken and transcribes in that
+	/// language. Costs an extra encoder pass over the first 30 seconds.
+	pub fn transcribe_multi_lang(
+		&self,
+		samples: &[f32],
+		options: &TranscribeOptions,
+		candidates: &[String],
+		cancel: &AtomicBool,
+	) -> Result<Transcription> {
+		if !self.is_multilingual || candidates.is_empty() {
+			return self.transcribe_detailed(samples, options, cancel);
+		}
+
+		let language = self.detect_among(samples, candidates)?;
+		let options = TranscribeOptions {
+			language: Some(language),
+			..*options
+		};
+		self.transcribe_detailed(samples, &options, cancel)
+	}
+
+	fn detect_among<'c>(&self, samples: &[f32], candidates: &'c [String]) -> Result<&'c str> {
+		let threads = num_cpus().max(1) as usize;
+		let (mut state, _) = self.create_state()?;
+		state
+			.pcm_to_mel(samples, threads)
+			.context("Failed to compute spectrogram")?;
+		let (_, probabilities) = state
+			.lang_detect(0, threads)
+			.context("Failed to detect language")?;
+
+		best_candidate(candidates, &probabilities)
+			.ok_or_else(|| anyhow::anyhow!("None of the candidate languages are known"))
+	}
+
+	/// Transcribes a WAV file, resampling it as needed
+	pub fn transcribe_file(
+		&self,
+		path: &Path,
+		options: &TranscribeOptions,
+		cancel: &AtomicBool,
+	) -> Result<String> {
+		let samples = crate::audio::read_wav_file(path)?;
+		self.transcribe(&samples, options, cancel)
+	}
+
+	/// Transcribes `duration_ms` of a WAV file starting at `start_ms`, a
+	/// duration of 0 runs to the end of the file.
+	pub fn transcribe_file_range(
+		&self,
+		path: &Path,
+		start_ms: u32,
+		duration_ms: u32,
+		options: &TranscribeOptions,
+		cancel: &AtomicBool,
+	) -> Result<String> {
+		let samples = crate::audio::read_wav_file(path)?;
+		let clip_ms = (samples.len() as u64 * 1000 / TRANSCRIPTION_SAMPLE_RATE as u64) as u32;
+		validate_range(clip_ms, start_ms, duration_ms)?;
+
+		let options = TranscribeOptions {
+			offset_ms: start_ms,
+			duration_ms,
+			..*options
+		};
+		self.transcribe(&samples, &options, cancel)
+	}
+
+	/// Transcribes `samples` once with each thread count in `counts` and
+	/// times the runs
+	pub fn benchmark_threads(
+		&self,
+		samples: &[f32],
+		counts: &[i32],
+		cancel: &AtomicBool,
+	) -> Result<Vec<ThreadBench>> {
+		let options = TranscribeOptions {
+			language: Some("en"),
+			..TranscribeOptions::default()
+		};
+		let audio_secs = samples.len() as f64 / TRANSCRIPTION_SAMPLE_RATE as f64;
+
+		// The first run pays for allocating buffers, keep it out of the timings
+		self.transcribe_detailed(samples, &options, cancel)?;
+
+		let mut results = Vec::with_capacity(counts.len());
+		for &n_threads in counts {
+			let options = TranscribeOptions {
+				n_threads: Some(n_threads),
+				..options
+			};
+			let started = Instant::now();
+			self.transcribe_detailed(samples, &options, cancel)?;
+			let elapsed = started.elapsed();
+
+			results.push(ThreadBench {
+				n_threads,
+				duration_ms: elapsed.as_millis() as u64,
+				realtime_factor: elapsed.as_secs_f64() / audio_secs,
+			});
+		}
+		Ok(results)
 	}
 
 	pub fn is_multilingual(&self) -> bool {
 		self.is_multilingual
 	}
+
+	/// Number of tokens `text` is split into by the model's tokenizer
+	pub fn count_tokens(&self, text: &str) -> Result<usize> {
+		// Every token covers at least one byte
+		let tokens = self
+			.ctx
+			.tokenize(text, text.len() + 1)
+			.context("Failed to tokenize text")?;
+		Ok(tokens.len())
+	}
+
+	pub fn model_path(&self) -> &str {
+		&self.model_path
+	}
+
+	/// The GPU device the model runs on, or None when running on CPU
+	pub fn gpu_device(&self) -> Option<i32> {
+		self.gpu_device
+	}
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -128,6 +484,11 @@ pub fn get_supported_languages() -> Vec<LanguageInfo> {
 	languages
 }
 
+/// Whether `code` is a language Whisper can be told to transcribe
+pub fn is_supported_language(code: &str) -> bool {
+	get_lang_id(code).is_some()
+}
+
 fn capitalize_first(s: &str) -> String {
 	let mut chars = s.chars();
 	match chars.next() {
@@ -136,7 +497,61 @@ fn capitalize_first(s: &str) -> String {
 	}
 }
 
-fn num_cpus() -> i32 {
+/// Picks the candidate language code with the highest detection probability
+fn best_candidate<'c>(candidates: &'c [String], probabilities: &[f32]) -> Option<&'c str> {
+	candidates
+		.iter()
+		.filter_map(|code| {
+			let id = get_lang_id(code)?;
+			let probability = *probabilities.get(usize::try_from(id).ok()?)?;
+			Some((code.as_str(), probability))
+		})
+		.max_by(|a, b| a.1.total_cmp(&b.1))
+		.map(|(code, _)| code)
+}
+
+/// Formats a segment timestamp, given in centiseconds as whisper reports them
+fn format_segment_time(centiseconds: i64, format: TimestampFormat) -> String {
+	let total_secs = centiseconds.max(0) / 100;
+	match format {
+		TimestampFormat::MinutesSeconds => format!("{:02}:{:02}", total_secs / 60, total_secs % 60),
+		TimestampFormat::HoursMinutesSeconds => format!(
+			"{:02}:{:02}:{:02}",
+			total_secs / 3600,
+			total_secs % 3600 / 60,
+			total_secs % 60
+		),
+	}
+}
+
+/// Whether the pause between a segment ending and the next starting, both
+/// in centiseconds, is long enough to suggest someone else is speaking
+fn is_speaker_gap(previous_end: i64, start: i64, gap_ms: u32) -> bool {
+	(start - previous_end) * 10 >= i64::from(gap_ms)
+}
+
+/// Checks that a range lies within a clip of `clip_ms` milliseconds
+fn validate_range(clip_ms: u32, start_ms: u32, duration_ms: u32) -> Result<()> {
+	if start_ms >= clip_ms {
+		anyhow::bail!("Start {} ms is past the end of the clip ({} ms)", start_ms, clip_ms);
+	}
+	if start_ms as u64 + duration_ms as u64 > clip_ms as u64 {
+		anyhow::bail!(
+			"Range {}-{} ms exceeds the clip length ({} ms)",
+			start_ms,
+			start_ms as u64 + duration_ms as u64,
+			clip_ms
+		);
+	}
+	Ok(())
+}
+
+unsafe extern "C" fn abort_callback(user_data: *mut c_void) -> bool {
+	let cancel = unsafe { &*(user_data as *const AtomicBool) };
+	cancel.load(Ordering::SeqCst)
+}
+
+pub fn num_cpus() -> i32 {
 	std::thread::available_parallelism()
 		.map(|p| p.get() as i32)
 		.unwrap_or(4)
@@ -163,6 +578,45 @@ mod tests {
 		assert_eq!(capitalize_first("English"), "English");
 	}
 
+	#[test]
+	fn test_best_candidate_picks_most_probable() {
+		let mut probabilities = vec![0.0; (get_lang_max_id() + 1) as usize];
+		probabilities[get_lang_id("it").unwrap() as usize] = 0.3;
+		probabilities[get_lang_id("es").unwrap() as usize] = 0.6;
+		probabilities[get_lang_id("en").unwrap() as usize] = 0.9;
+
+		let candidates = vec!["it".to_string(), "es".to_string(), "xx".to_string()];
+		assert_eq!(best_candidate(&candidates, &probabilities), Some("es"));
+		assert_eq!(best_candidate(&["xx".to_string()], &probabilities), None);
+	}
+
+	#[test]
+	fn test_format_segment_time() {
+		assert_eq!(format_segment_time(1234, TimestampFormat::MinutesSeconds), "00:12");
+		assert_eq!(format_segment_time(372_000, TimestampFormat::MinutesSeconds), "62:00");
+		assert_eq!(
+			format_segment_time(372_000, TimestampFormat::HoursMinutesSeconds),
+			"01:02:00"
+		);
+		assert_eq!(format_segment_time(-5, TimestampFormat::MinutesSeconds), "00:00");
+	}
+
+	#[test]
+	fn test_is_speaker_gap() {
+		assert!(is_speaker_gap(500, 650, 1500));
+		assert!(!is_speaker_gap(500, 640, 1500));
+		// Overlapping segments are never a gap
+		assert!(!is_speaker_gap(500, 480, 100));
+	}
+
+	#[test]
+	fn test_validate_range() {
+		assert!(validate_range(10_000, 0, 0).is_ok());
+		assert!(validate_range(10_000, 2_000, 8_000).is_ok());
+		assert!(validate_range(10_000, 10_000, 0).is_err());
+		assert!(validate_range(10_000, 5_000, 6_000).is_err());
+	}
+
 	#[test]
 	fn test_supported_languages_not_empty() {
 		let languages = get_supported_languages();
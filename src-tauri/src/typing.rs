@@ -0,0 +1,25 @@
+use anyhow::{Context, Result};
+use enigo::{Enigo, Keyboard, Settings};
+use std::thread;
+use std::time::Duration;
+
+/// Types `text` into the focused window as keystrokes. `start_delay` gives
+/// the window time to take input and a non-zero `char_delay` types one
+/// character at a time for apps that drop fast input.
+pub fn type_text(text: &str, start_delay: Duration, char_delay: Duration) -> Result<()> {
+	let mut enigo = Enigo::new(&Settings::default()).context("Failed to connect to the input system")?;
+	thread::sleep(start_delay);
+
+	if char_delay.is_zero() {
+		return enigo.text(text).context("Failed to type text");
+	}
+
+	let mut buffer = [0u8; 4];
+	for c in text.chars() {
+		enigo
+			.text(c.encode_utf8(&mut buffer))
+			.context("Failed to type text")?;
+		thread::sleep(char_delay);
+	}
+	Ok(())
+}
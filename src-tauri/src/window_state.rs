@@ -0,0 +1,105 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// How much of the window, in pixels along each axis, has to be on a
+/// monitor for a saved position to be restored
+const MIN_VISIBLE_PX: i64 = 64;
+
+/// Position and size of the main window, in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+	pub x: i32,
+	pub y: i32,
+	pub width: u32,
+	pub height: u32,
+}
+
+/// Area covered by a monitor, in physical pixels
+#[derive(Debug, Clone, Copy)]
+pub struct MonitorArea {
+	pub x: i32,
+	pub y: i32,
+	pub width: u32,
+	pub height: u32,
+}
+
+impl WindowGeometry {
+	pub fn path() -> Result<PathBuf> {
+		Ok(Config::config_dir()?.join("window_state.json"))
+	}
+
+	pub fn load() -> Result<Option<Self>> {
+		let path = Self::path()?;
+		if !path.exists() {
+			return Ok(None);
+		}
+
+		let content = fs::read_to_string(&path).context("Failed to read window state")?;
+		serde_json::from_str(&content)
+			.map(Some)
+			.context("Failed to parse window state")
+	}
+
+	pub fn save(&self) -> Result<()> {
+		let content = serde_json::to_string_pretty(self).context("Failed to serialize window state")?;
+		fs::write(Self::path()?, content).context("Failed to write window state")?;
+		Ok(())
+	}
+
+	/// Whether enough of the window, including its top edge, lands on one
+	/// of `monitors` for the user to grab it again. Fails when the monitor
+	/// it was on has been disconnected.
+	pub fn is_visible_on(&self, monitors: &[MonitorArea]) -> bool {
+		let (left, top) = (self.x as i64, self.y as i64);
+		let (right, bottom) = (left + self.width as i64, top + self.height as i64);
+
+		monitors.iter().any(|monitor| {
+			let (m_left, m_top) = (monitor.x as i64, monitor.y as i64);
+			let m_right = m_left + monitor.width as i64;
+			let m_bottom = m_top + monitor.height as i64;
+
+			let overlap_x = right.min(m_right) - left.max(m_left);
+			let overlap_y = bottom.min(m_bottom) - top.max(m_top);
+			overlap_x >= MIN_VISIBLE_PX
+				&& overlap_y >= MIN_VISIBLE_PX
+				&& (m_top..m_bottom).contains(&top)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MONITORS: [MonitorArea; 2] = [
+		MonitorArea { x: 0, y: 0, width: 1920, height: 1080 },
+		MonitorArea { x: 1920, y: 0, width: 1280, height: 1024 },
+	];
+
+	fn window(x: i32, y: i32) -> WindowGeometry {
+		WindowGeometry { x, y, width: 500, height: 600 }
+	}
+
+	#[test]
+	fn test_visible_on_either_monitor() {
+		assert!(window(100, 100).is_visible_on(&MONITORS));
+		assert!(window(2500, 200).is_visible_on(&MONITORS));
+		// Straddling the two monitors
+		assert!(window(1700, 100).is_visible_on(&MONITORS));
+	}
+
+	#[test]
+	fn test_off_screen_positions_are_rejected() {
+		// Left behind on a disconnected monitor
+		assert!(!window(3500, 100).is_visible_on(&MONITORS));
+		assert!(!window(-480, 100).is_visible_on(&MONITORS));
+		// Title bar above the top of the screen
+		assert!(!window(100, -50).is_visible_on(&MONITORS));
+		// Windows parks minimized windows here
+		assert!(!window(-32000, -32000).is_visible_on(&MONITORS));
+		assert!(!window(100, 100).is_visible_on(&[]));
+	}
+}
@@ -7,9 +7,11 @@ import { StatusIndicator } from "./components/StatusIndicator"
 import { ThemeSwitch } from "./components/ThemeSwitch"
 import { TranscriptionSection } from "./components/TranscriptionSection"
 import {
+	handleExportHistory,
 	handleLoadModel,
 	handleSelectModel,
 	openModelUrl,
+	resolveReview,
 	saveConfig
 } from "./handlers"
 import { useAppState } from "./hooks/useAppState"
@@ -28,6 +30,8 @@ function App() {
 		lastTranscription,
 		error,
 		setError,
+		toast,
+		pendingReview,
 		modelsDir,
 		theme,
 		setTheme,
@@ -46,6 +50,7 @@ function App() {
 			loadInitialData
 		)
 	const openModelUrlWrapper = () => openModelUrl()
+	const exportHistoryWrapper = () => handleExportHistory(setError)
 	const saveConfigWrapper = async (newConfig: Config) => {
 		const success = await saveConfig(newConfig, setError)
 		if (success) {
@@ -63,7 +68,32 @@ function App() {
 
 			{error && <div class="error-message">{error}</div>}
 
-			<TranscriptionSection lastTranscription={lastTranscription} />
+			{toast && (
+				<div class="toast" role="status">
+					<strong>{toast.title}</strong>
+					<span>{toast.body}</span>
+				</div>
+			)}
+
+			{pendingReview !== null && (
+				<div class="review-panel">
+					<h3>Review transcription</h3>
+					<p class="review-text">{pendingReview}</p>
+					<div class="review-actions">
+						<button onClick={() => resolveReview(false, setError)}>
+							Discard
+						</button>
+						<button onClick={() => resolveReview(true, setError)}>
+							Accept
+						</button>
+					</div>
+				</div>
+			)}
+
+			<TranscriptionSection
+				lastTranscription={lastTranscription}
+				exportHistory={exportHistoryWrapper}
+			/>
 
 			<ModelSection
 				hasModel={hasModel}
@@ -0,0 +1,55 @@
+import { invoke } from "@tauri-apps/api/core"
+import { listen } from "@tauri-apps/api/event"
+import { writeText } from "@tauri-apps/plugin-clipboard-manager"
+import { useEffect, useState } from "preact/hooks"
+import "../App.css"
+import { getInitialTheme } from "../utils"
+
+declare global {
+	interface Window {
+		__EDITOR_TEXT__?: string | null
+	}
+}
+
+export function Editor() {
+	const [text, setText] = useState("")
+	const [copied, setCopied] = useState(false)
+
+	useEffect(() => {
+		document.documentElement.setAttribute("data-theme", getInitialTheme())
+		if (typeof window.__EDITOR_TEXT__ === "string") {
+			setText(window.__EDITOR_TEXT__)
+		} else {
+			invoke<string>("get_last_transcription").then(setText)
+		}
+
+		const unlisten = listen<string>("editor-text", (event) => {
+			setText(event.payload)
+			setCopied(false)
+		})
+		return () => {
+			unlisten.then((fn) => fn())
+		}
+	}, [])
+
+	async function handleCopy() {
+		await writeText(text)
+		setCopied(true)
+	}
+
+	return (
+		<div class="app editor">
+			<textarea
+				class="transcription-box editor-text"
+				value={text}
+				onInput={(e) => {
+					setText(e.currentTarget.value)
+					setCopied(false)
+				}}
+			/>
+			<button type="button" class="primary" onClick={handleCopy}>
+				{copied ? "Copied" : "Copy"}
+			</button>
+		</div>
+	)
+}
@@ -68,7 +68,7 @@ export function ModelSection({
 					>
 						<option value="">Select a model...</option>
 						{models.map((model) => (
-							<option key={model.path} value={model.path}>
+							<option key={model.path} value={model.path} title={model.source_dir}>
 								{model.name} ({formatFileSize(model.size)})
 							</option>
 						))}
@@ -2,7 +2,7 @@ import { invoke } from "@tauri-apps/api/core"
 import { listen } from "@tauri-apps/api/event"
 import { sendNotification } from "@tauri-apps/plugin-notification"
 import { useCallback, useEffect, useState } from "preact/hooks"
-import type { Config, GpuDevice, LanguageInfo } from "../types"
+import type { Config, GpuDevice, KeyInfo, LanguageInfo } from "../types"
 
 interface SettingsSectionProps {
 	config: Config
@@ -12,59 +12,10 @@ interface SettingsSectionProps {
 	saveConfig: (config: Config) => Promise<void>
 }
 
-const VALID_KEYS = new Set([
-	"F1",
-	"F2",
-	"F3",
-	"F4",
-	"F5",
-	"F6",
-	"F7",
-	"F8",
-	"F9",
-	"F10",
-	"F11",
-	"F12",
-	"A",
-	"B",
-	"C",
-	"D",
-	"E",
-	"F",
-	"G",
-	"H",
-	"I",
-	"J",
-	"K",
-	"L",
-	"M",
-	"N",
-	"O",
-	"P",
-	"Q",
-	"R",
-	"S",
-	"T",
-	"U",
-	"V",
-	"W",
-	"X",
-	"Y",
-	"Z",
-	"0",
-	"1",
-	"2",
-	"3",
-	"4",
-	"5",
-	"6",
-	"7",
-	"8",
-	"9",
-	"Space"
-])
-
-function keyEventToHotkey(e: KeyboardEvent): string | null {
+function keyEventToHotkey(
+	e: KeyboardEvent,
+	validKeys: Set<string>
+): string | null {
 	const modifiers: string[] = []
 
 	if (e.ctrlKey) modifiers.push("Ctrl")
@@ -86,7 +37,7 @@ function keyEventToHotkey(e: KeyboardEvent): string | null {
 		key = "Space"
 	}
 
-	if (key && VALID_KEYS.has(key)) {
+	if (key && validKeys.has(key)) {
 		// Require a modifier for non-function keys
 		if (!isFunctionKey && modifiers.length === 0) {
 			return null
@@ -107,6 +58,7 @@ export function SettingsSection({
 	const [pendingConfig, setPendingConfig] = useState<Config>(config)
 	const [isRecording, setIsRecording] = useState(false)
 	const [gpuDevices, setGpuDevices] = useState<GpuDevice[]>([])
+	const [bindableKeys, setBindableKeys] = useState<Set<string>>(new Set())
 
 	useEffect(() => {
 		setPendingConfig(config)
@@ -117,6 +69,15 @@ export function SettingsSection({
 		invoke<GpuDevice[]>("get_gpu_devices").then(setGpuDevices)
 	}, [])
 
+	// Fetch the keys the backend can parse so the recorder never offers others
+	useEffect(() => {
+		invoke<KeyInfo[]>("get_bindable_keys").then((keys) =>
+			setBindableKeys(
+				new Set(keys.filter((k) => !k.modifier).map((k) => k.token))
+			)
+		)
+	}, [])
+
 	// Listen for GPU fallback event and refetch config
 	useEffect(() => {
 		const unlisten = listen("gpu-fallback", async () => {
@@ -137,12 +98,12 @@ export function SettingsSection({
 			return
 		}
 
-		const hotkey = keyEventToHotkey(e)
+		const hotkey = keyEventToHotkey(e, bindableKeys)
 		if (hotkey) {
 			setPendingConfig((prev) => ({ ...prev, hotkey }))
 			setIsRecording(false)
 		}
-	}, [])
+	}, [bindableKeys])
 
 	useEffect(() => {
 		if (isRecording) {
@@ -151,6 +112,8 @@ export function SettingsSection({
 		}
 	}, [isRecording, handleKeyDown])
 
+	const hasUsableGpu = gpuDevices.some((d) => d.whisper_backend !== null)
+
 	const availableLanguages = isMultilingual
 		? [{ code: "auto", name: "Auto-detect" }, ...supportedLanguages]
 		: supportedLanguages.filter((lang) => lang.code === "en")
@@ -212,7 +175,7 @@ export function SettingsSection({
 
 		await saveConfig(pendingConfig)
 
-		if (changes.length > 0) {
+		if (changes.length > 0 && !pendingConfig.silent_mode) {
 			try {
 				sendNotification({
 					title: "Settings Updated",
@@ -304,7 +267,7 @@ export function SettingsSection({
 						type="checkbox"
 						id="use-gpu"
 						checked={pendingConfig.use_gpu}
-						disabled={gpuDevices.length === 0}
+						disabled={!hasUsableGpu}
 						onChange={(e) =>
 							setPendingConfig({
 								...pendingConfig,
@@ -313,7 +276,7 @@ export function SettingsSection({
 						}
 					/>
 					<label for="use-gpu">Use GPU acceleration</label>
-					{gpuDevices.length === 0 && (
+					{!hasUsableGpu && (
 						<span class="setting-hint" style={{ color: "red" }}>
 							No GPU devices available
 						</span>
@@ -326,7 +289,7 @@ export function SettingsSection({
 						id="gpu-device-select"
 						value={pendingConfig.gpu_device}
 						title={getGpuDeviceLabel(pendingConfig.gpu_device)}
-						disabled={!pendingConfig.use_gpu || gpuDevices.length === 0}
+						disabled={!pendingConfig.use_gpu || !hasUsableGpu}
 						onChange={(e) =>
 							setPendingConfig({
 								...pendingConfig,
@@ -334,10 +297,18 @@ export function SettingsSection({
 							})
 						}
 					>
-						{gpuDevices.map((device) => {
-							const label = `${device.name} (${device.backend})`
+						{gpuDevices.map((device, index) => {
+							const usable = device.whisper_backend !== null
+							const label = usable
+								? `${device.name} (${device.backend})`
+								: `${device.name} (${device.backend}, not supported by this build)`
 							return (
-								<option key={device.id} value={device.id} title={label}>
+								<option
+									key={`${device.id}-${index}`}
+									value={device.id}
+									title={label}
+									disabled={!usable}
+								>
 									{label}
 								</option>
 							)
@@ -1,9 +1,11 @@
 interface TranscriptionSectionProps {
 	lastTranscription: string
+	exportHistory: () => void
 }
 
 export function TranscriptionSection({
-	lastTranscription
+	lastTranscription,
+	exportHistory
 }: TranscriptionSectionProps) {
 	return (
 		<div class="section">
@@ -12,6 +14,9 @@ export function TranscriptionSection({
 				{lastTranscription ||
 					"No transcription yet. Press the hotkey to start recording."}
 			</div>
+			<button type="button" onClick={exportHistory}>
+				Export history
+			</button>
 		</div>
 	)
 }
@@ -1,8 +1,19 @@
 import { invoke } from "@tauri-apps/api/core"
-import { open } from "@tauri-apps/plugin-dialog"
+import { open, save } from "@tauri-apps/plugin-dialog"
 import { openUrl } from "@tauri-apps/plugin-opener"
 import type { Config } from "./types"
 
+export async function resolveReview(
+	accept: boolean,
+	setError: (value: string | null) => void
+) {
+	try {
+		await invoke("resolve_review", { accept })
+	} catch (e) {
+		setError(String(e))
+	}
+}
+
 export async function handleLoadModel(
 	setHasModel: (value: boolean) => void,
 	setError: (value: string | null) => void,
@@ -97,3 +108,26 @@ export async function openModelUrl() {
 		console.error("Failed to open URL:", e)
 	}
 }
+
+export async function handleExportHistory(
+	setError: (value: string | null) => void
+) {
+	try {
+		const path = await save({
+			defaultPath: "history.csv",
+			filters: [
+				{ name: "CSV", extensions: ["csv"] },
+				{ name: "Text", extensions: ["txt"] },
+				{ name: "JSON", extensions: ["json"] }
+			]
+		})
+
+		if (path) {
+			const format = path.split(".").pop()?.toLowerCase() ?? "txt"
+			await invoke("export_history", { path, format })
+			setError(null)
+		}
+	} catch (e) {
+		setError(String(e))
+	}
+}
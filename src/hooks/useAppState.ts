@@ -1,7 +1,14 @@
 import { invoke } from "@tauri-apps/api/core"
 import { listen } from "@tauri-apps/api/event"
 import { useEffect, useState } from "preact/hooks"
-import type { AppState, Config, LanguageInfo, ModelInfo, Theme } from "../types"
+import type {
+	AppState,
+	Config,
+	LanguageInfo,
+	ModelInfo,
+	NotificationToast,
+	Theme
+} from "../types"
 import { getInitialTheme } from "../utils"
 
 export function useAppState() {
@@ -12,6 +19,8 @@ export function useAppState() {
 	const [isMultilingual, setIsMultilingual] = useState(false)
 	const [lastTranscription, setLastTranscription] = useState("")
 	const [error, setError] = useState<string | null>(null)
+	const [toast, setToast] = useState<NotificationToast | null>(null)
+	const [pendingReview, setPendingReview] = useState<string | null>(null)
 	const [modelsDir, setModelsDir] = useState("")
 	const [theme, setTheme] = useState<Theme>(getInitialTheme)
 	const [supportedLanguages, setSupportedLanguages] = useState<LanguageInfo[]>(
@@ -73,6 +82,42 @@ export function useAppState() {
 		listen<string>("error", (event) => {
 			setError(event.payload)
 		})
+
+		// System notifications failed, the backend sends them here instead
+		listen<NotificationToast>("notification-toast", (event) => {
+			setToast(event.payload)
+			setTimeout(
+				() => setToast((current) => (current === event.payload ? null : current)),
+				5000
+			)
+		})
+
+		// Long transcriptions wait for Accept/Discard before being copied
+		listen<string>("review-transcription", (event) => {
+			setPendingReview(event.payload)
+		})
+
+		listen<boolean>("review-closed", () => {
+			setPendingReview(null)
+		})
+
+		listen<string>("language-changed", (event) => {
+			setConfig((current) =>
+				current
+					? {
+							...current,
+							previous_language: current.language,
+							language: event.payload
+						}
+					: current
+			)
+		})
+
+		listen<boolean>("auto-copy-changed", (event) => {
+			setConfig((current) =>
+				current ? { ...current, auto_copy: event.payload } : current
+			)
+		})
 	}
 
 	return {
@@ -87,6 +132,8 @@ export function useAppState() {
 		lastTranscription,
 		error,
 		setError,
+		toast,
+		pendingReview,
 		modelsDir,
 		theme,
 		setTheme,
@@ -1,7 +1,8 @@
 import { render } from "preact"
 import App from "./App"
+import { Editor } from "./components/Editor"
 
 const root = document.getElementById("root")
 if (root) {
-	render(<App />, root)
+	render(window.location.hash === "#editor" ? <Editor /> : <App />, root)
 }
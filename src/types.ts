@@ -1,13 +1,192 @@
-export type AppState = "idle" | "recording" | "transcribing"
+export type AppState = "idle" | "recording" | "transcribing" | "calibrating"
+
+export type OutputMode = "clipboard" | "editor" | "target_app" | "type"
+
+export type OutputFormat = "plain" | "markdown_quote" | "bullet"
+
+export type ClipboardFormat = "plain" | "html"
+
+export type TimestampFormat = "mm:ss" | "hh:mm:ss"
+
+export type EmptyResultAction = "notify" | "silent" | "retry_louder_hint"
+
+export type HotkeyAction =
+	| "toggle_recording"
+	| "toggle_auto_copy"
+	| "swap_language"
+
+export type NotificationLevel = "all" | "errors" | "none"
 
 export interface Config {
 	hotkey: string
 	language: string
+	previous_language: string | null
 	model_path: string | null
+	model_name: string | null
 	auto_copy: boolean
 	show_notifications: boolean
 	use_gpu: boolean
 	gpu_device: number
+	gpu_backend: string
+	start_minimized: boolean
+	autostart: boolean
+	correction_wordlist: string | null
+	acronyms: string[]
+	initial_prompt: string | null
+	vocabulary_file: string | null
+	auto_stop_silence_ms: number | null
+	fallback_models: string[]
+	carry_context: boolean
+	min_interval_between_transcriptions_ms: number | null
+	output_mode: OutputMode
+	capture_sample_rate: number | null
+	notification_level: NotificationLevel
+	notification_sound: boolean
+	transcription_log: string | null
+	output_template: string
+	output_format: OutputFormat
+	clipboard_format: ClipboardFormat
+	extra_model_dirs: string[]
+	inline_timestamps: boolean
+	timestamp_format: TimestampFormat
+	no_speech_threshold: number | null
+	on_empty_result: EmptyResultAction
+	input_gain_db: number
+	lock_detected_language: boolean
+	audio_host: string | null
+	action_hotkeys: Record<string, HotkeyAction>
+	multi_language_detection: boolean
+	candidate_languages: string[]
+	transcription_timeout_secs: number | null
+	keep_stream_open: boolean
+	temperature: number
+	temperature_inc: number
+	entropy_threshold: number
+	logprob_threshold: number
+	repetition_warning_ratio: number | null
+	auto_end_punctuation: boolean
+	target_app: string | null
+	type_start_delay_ms: number
+	type_char_delay_ms: number
+	stream_pcm: boolean
+	silent_mode: boolean
+	audio_buffer_frames: number | null
+	post_command: string | null
+	speaker_gap_ms: number | null
+	save_recordings: boolean
+	max_saved_recordings: number | null
+	n_threads: number | null
+	model_settings: Record<string, ModelSettings>
+	tag_language: boolean
+	preferred_devices: string[]
+	loopback_devices: string[]
+	adaptive_power: boolean
+	low_power_model: string | null
+	confirm_above_chars: number | null
+	review_timeout_secs: number
+	split_sentences: boolean
+	sentence_delay_ms: number
+	restore_previous_focus: boolean
+	continuous_mode: boolean
+	segment_separator: string | null
+}
+
+export interface EffectiveConfig {
+	config: Config
+	n_threads: number
+	device: string
+	gpu_active: boolean
+	forced_english: boolean
+	loaded_model_path: string | null
+	model_multilingual: boolean | null
+}
+
+export interface PcmFrame {
+	sample_rate: number
+	samples: number[]
+}
+
+export interface KeyInfo {
+	token: string
+	aliases: string[]
+	modifier: boolean
+}
+
+export interface Diagnostics {
+	app_version: string
+	os: string
+	arch: string
+	state: AppState
+	effective_config: EffectiveConfig
+	loaded_model: LoadedModel | null
+	gpu_devices: GpuDevice[]
+	audio: AudioHostInfo
+	last_error: string | null
+}
+
+export interface NotificationToast {
+	title: string
+	body: string
+}
+
+export interface ModelSettings {
+	n_threads: number | null
+}
+
+export interface GpuStatus {
+	requested: boolean
+	active: boolean
+	device_name: string | null
+	fell_back: boolean
+}
+
+export interface AlignedWord {
+	op: "match" | "substitution" | "deletion" | "insertion"
+	reference: string | null
+	hypothesis: string | null
+}
+
+export interface WerResult {
+	wer: number
+	substitutions: number
+	deletions: number
+	insertions: number
+	reference_words: number
+	alignment: AlignedWord[]
+}
+
+export interface PowerModelSwitch {
+	on_battery: boolean
+	model_path: string
+}
+
+export interface ThreadBench {
+	n_threads: number
+	duration_ms: number
+	realtime_factor: number
+}
+
+export interface TranscriptionSentence {
+	index: number
+	total: number
+	text: string
+}
+
+export type MicPermission = "granted" | "denied" | "undetermined"
+
+export type StateRecovery = "retried" | "cpu_fallback"
+
+export interface HotkeyPreset {
+	id: string
+	hotkey: string
+	label: string
+	conflict: string | null
+}
+
+export interface LoadedModel {
+	path: string
+	name: string
+	multilingual: boolean
 }
 
 export interface GpuDevice {
@@ -15,12 +194,89 @@ export interface GpuDevice {
 	name: string
 	device_type: string
 	backend: string
+	whisper_backend: string | null
+	memory_mb: number | null
+}
+
+export interface AudioHostInfo {
+	host: string
+	default_input_device: string | null
+	available_hosts: string[]
+}
+
+export interface DeviceDetails {
+	name: string
+	default_sample_rate: number | null
+	channels: number | null
+	supported_sample_formats: string[]
+	is_default: boolean
+	is_loopback: boolean
+}
+
+export interface RecommendedModel {
+	name: string
+	url: string
+}
+
+export interface MachineResources {
+	free_disk_mb: number | null
+	memory_mb: number | null
+	gpu_name: string | null
+	gpu_memory_mb: number | null
+}
+
+export interface DownloadSuggestion {
+	model: RecommendedModel | null
+	explanation: string
+	resources: MachineResources
+}
+
+export interface ModelRecommendation {
+	language: string
+	speed: RecommendedModel
+	quality: RecommendedModel
+}
+
+export interface SetupProgress {
+	stage: "downloading" | "verifying" | "loading" | "done"
+	downloaded: number
+	total: number | null
+}
+
+export interface DownloadProgress {
+	downloaded_bytes: number
+	total_bytes: number | null
+	bytes_per_sec: number
+	eta_secs: number | null
+}
+
+export interface RecordReadiness {
+	ready: boolean
+	reasons: string[]
 }
 
 export interface ModelInfo {
 	name: string
 	path: string
 	size: number
+	source_dir: string
+	metadata: ModelMetadata | null
+}
+
+export interface ModelValidation {
+	path: string
+	ok: boolean
+	error: string | null
+}
+
+export interface ModelMetadata {
+	model_type: string
+	multilingual: boolean
+	n_vocab: number
+	n_mels: number
+	n_audio_layer: number
+	n_text_layer: number
+	quantization: string
 }
 
 export interface LanguageInfo {
@@ -29,3 +285,21 @@ export interface LanguageInfo {
 }
 
 export type Theme = "light" | "dark"
+
+export interface Stats {
+	total_recordings: number
+	total_audio_secs: number
+	total_words: number
+	total_transcription_secs: number
+}
+
+export interface LengthBucket {
+	min_words: number
+	max_words: number | null
+	count: number
+}
+
+export interface HistoryEntry {
+	timestamp: number
+	text: string
+}
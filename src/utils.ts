@@ -16,6 +16,8 @@ export function getStatusText(appState: AppState): string {
 			return "Recording..."
 		case "transcribing":
 			return "Transcribing..."
+		case "calibrating":
+			return "Measuring noise..."
 	}
 }
 